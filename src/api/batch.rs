@@ -0,0 +1,90 @@
+use futures::future::join_all;
+use serde_json::{json, Value};
+use yerpc::{MessageHandle, RpcServer};
+
+use super::CommandApi;
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+
+/// Handles a json-rpc message like [`MessageHandle::handle_message`],
+/// but also supports batches (arrays of requests).
+///
+/// The requests of a batch are executed concurrently and their responses
+/// are returned as one array, like json-rpc 2.0 requires.
+/// Single messages are passed on to the `MessageHandle`, which sends the response itself,
+/// so `None` is returned for them, as well as for batches that only contain notifications.
+pub async fn handle_message(
+    handle: &MessageHandle<CommandApi>,
+    api: &CommandApi,
+    message: &str,
+) -> Option<String> {
+    if !message.trim_start().starts_with('[') {
+        handle.handle_message(message).await;
+        return None;
+    }
+
+    let response = match serde_json::from_str::<Vec<Value>>(message) {
+        Ok(batch) if batch.is_empty() => {
+            error_response(Value::Null, INVALID_REQUEST, "Invalid Request", None)
+        }
+        Ok(batch) => {
+            let responses: Vec<Value> = join_all(
+                batch
+                    .into_iter()
+                    .map(|request| handle_request(api, request)),
+            )
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+            if responses.is_empty() {
+                return None;
+            }
+            Value::Array(responses)
+        }
+        Err(_) => error_response(Value::Null, PARSE_ERROR, "Parse error", None),
+    };
+    Some(response.to_string())
+}
+
+/// Handles one request of a batch, returns `None` for notifications.
+async fn handle_request(api: &CommandApi, request: Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let method = match request.get("method").and_then(Value::as_str) {
+        Some(method) => method.to_owned(),
+        None => {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                INVALID_REQUEST,
+                "Invalid Request",
+                None,
+            ))
+        }
+    };
+    let params = request
+        .get("params")
+        .cloned()
+        .unwrap_or_else(|| Value::Array(Vec::new()));
+
+    match id {
+        None => {
+            if let Err(err) = api.handle_notification(method, params).await {
+                log::warn!("notification in batch failed: {}", err.message);
+            }
+            None
+        }
+        Some(id) => Some(match api.handle_request(method, params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(id, err.code, &err.message, err.data),
+        }),
+    }
+}
+
+fn error_response(id: Value, code: i32, message: &str, data: Option<Value>) -> Value {
+    let mut error = json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
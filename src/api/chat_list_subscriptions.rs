@@ -0,0 +1,317 @@
+use async_std::sync::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use typescript_type_def::TypeDef;
+
+use super::error::Result;
+use super::types::chat_list::{
+    get_chat_list_entries, get_chat_list_items, ChatListEntry, ChatListItemFetchResult,
+};
+
+/// A change of a subscribed chat list, sent in the `ChatListChanged` server event.
+///
+/// To apply the changes of one event, first remove the `Removed` and `Moved` chats,
+/// then insert the `Inserted` and `Moved` chats in the order of the event
+/// and finally replace the `Updated` items.
+#[derive(Serialize, TypeDef, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum ChatListChange {
+    #[serde(rename_all = "camelCase")]
+    Inserted {
+        index: usize,
+        chat_id: u32,
+        item: ChatListItemFetchResult,
+    },
+    #[serde(rename_all = "camelCase")]
+    Removed { chat_id: u32 },
+    #[serde(rename_all = "camelCase")]
+    Moved { index: usize, chat_id: u32 },
+    #[serde(rename_all = "camelCase")]
+    Updated {
+        chat_id: u32,
+        item: ChatListItemFetchResult,
+    },
+}
+
+/// The changes of a subscription, to be sent to the connection that made it.
+pub(crate) struct ChatListUpdate {
+    pub connection_id: u32,
+    pub subscription_id: u32,
+    pub changes: Vec<ChatListChange>,
+}
+
+/// The initial state of a chat list subscription.
+#[derive(Serialize, TypeDef)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatListSubscription {
+    subscription_id: u32,
+    entries: Vec<ChatListEntry>,
+    items: HashMap<u32, ChatListItemFetchResult>,
+}
+
+#[derive(Debug)]
+struct Subscription {
+    list_flags: Option<u32>,
+    query_string: Option<String>,
+    query_contact_id: Option<u32>,
+    entries: Vec<ChatListEntry>,
+    items: HashMap<u32, ChatListItemFetchResult>,
+}
+
+/// A subscription together with the account and the connection it belongs to,
+/// which are kept outside of the lock, so they can be checked while the subscription updates.
+#[derive(Debug)]
+struct SubscriptionEntry {
+    account_id: u32,
+    connection_id: u32,
+    subscription: Arc<Mutex<Subscription>>,
+}
+
+/// Keeps the last state of the subscribed chat lists, to compute what changed.
+///
+/// Subscriptions belong to the connection that made them
+/// and are removed with [`ChatListSubscriptions::remove_connection`] when it is closed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChatListSubscriptions {
+    next_id: Arc<AtomicU32>,
+    subscriptions: Arc<std::sync::Mutex<HashMap<u32, SubscriptionEntry>>>,
+}
+
+impl ChatListSubscriptions {
+    pub async fn subscribe(
+        &self,
+        ctx: &deltachat::context::Context,
+        connection_id: u32,
+        account_id: u32,
+        list_flags: Option<u32>,
+        query_string: Option<String>,
+        query_contact_id: Option<u32>,
+    ) -> Result<ChatListSubscription> {
+        let entries =
+            get_chat_list_entries(ctx, list_flags, query_string.as_deref(), query_contact_id)
+                .await?;
+        let items = get_chat_list_items(ctx, &entries).await;
+
+        let subscription_id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let subscription = Subscription {
+            list_flags,
+            query_string,
+            query_contact_id,
+            entries: entries.clone(),
+            items: items.clone(),
+        };
+        self.subscriptions.lock().unwrap().insert(
+            subscription_id,
+            SubscriptionEntry {
+                account_id,
+                connection_id,
+                subscription: Arc::new(Mutex::new(subscription)),
+            },
+        );
+
+        Ok(ChatListSubscription {
+            subscription_id,
+            entries,
+            items,
+        })
+    }
+
+    /// Returns false if the connection has no subscription with this id.
+    pub fn unsubscribe(&self, connection_id: u32, subscription_id: u32) -> bool {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        match subscriptions.get(&subscription_id) {
+            Some(entry) if entry.connection_id == connection_id => {
+                subscriptions.remove(&subscription_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove_account(&self, account_id: u32) {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.account_id != account_id);
+    }
+
+    /// Removes the subscriptions of a closed connection.
+    pub fn remove_connection(&self, connection_id: u32) {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.connection_id != connection_id);
+    }
+
+    /// Reloads the subscribed chat lists of the account.
+    ///
+    /// Only the items of `chat_id` and of chats with a new last message are fetched again,
+    /// if `chat_id` is `None` the items of all chats are.
+    ///
+    /// Returns the changes per subscription, subscriptions without changes are left out.
+    pub async fn update(
+        &self,
+        ctx: &deltachat::context::Context,
+        account_id: u32,
+        chat_id: Option<u32>,
+    ) -> Result<Vec<ChatListUpdate>> {
+        let subscriptions: Vec<(u32, u32, Arc<Mutex<Subscription>>)> = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.account_id == account_id)
+            .map(|(id, entry)| (*id, entry.connection_id, entry.subscription.clone()))
+            .collect();
+
+        let mut result = Vec::new();
+        for (subscription_id, connection_id, subscription) in subscriptions {
+            let mut subscription = subscription.lock().await;
+            let changes = subscription.update(ctx, chat_id).await?;
+            if !changes.is_empty() {
+                result.push(ChatListUpdate {
+                    connection_id,
+                    subscription_id,
+                    changes,
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Subscription {
+    async fn update(
+        &mut self,
+        ctx: &deltachat::context::Context,
+        changed_chat_id: Option<u32>,
+    ) -> Result<Vec<ChatListChange>> {
+        let entries = get_chat_list_entries(
+            ctx,
+            self.list_flags,
+            self.query_string.as_deref(),
+            self.query_contact_id,
+        )
+        .await?;
+
+        let old_ids: Vec<u32> = self.entries.iter().map(|entry| entry.0).collect();
+        let new_ids: Vec<u32> = entries.iter().map(|entry| entry.0).collect();
+        let moved = moved_chats(&old_ids, &new_ids);
+        let old_entries: HashMap<u32, u32> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.0, entry.1))
+            .collect();
+
+        let mut changes = Vec::new();
+        let new_id_set: HashSet<u32> = new_ids.iter().copied().collect();
+        for chat_id in old_ids {
+            if !new_id_set.contains(&chat_id) {
+                self.items.remove(&chat_id);
+                changes.push(ChatListChange::Removed { chat_id });
+            }
+        }
+
+        let outdated: Vec<ChatListEntry> = entries
+            .iter()
+            .filter(|entry| match old_entries.get(&entry.0) {
+                Some(last_msg_id) => {
+                    *last_msg_id != entry.1 || changed_chat_id.map_or(true, |id| id == entry.0)
+                }
+                None => true,
+            })
+            .cloned()
+            .collect();
+        let mut fetched = get_chat_list_items(ctx, &outdated).await;
+
+        let mut updated = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let chat_id = entry.0;
+            let item = fetched.remove(&chat_id);
+            if !old_entries.contains_key(&chat_id) {
+                if let Some(item) = item {
+                    self.items.insert(chat_id, item.clone());
+                    changes.push(ChatListChange::Inserted {
+                        index,
+                        chat_id,
+                        item,
+                    });
+                }
+                continue;
+            }
+            if moved.contains(&chat_id) {
+                changes.push(ChatListChange::Moved { index, chat_id });
+            }
+            if let Some(item) = item {
+                if self.items.get(&chat_id) != Some(&item) {
+                    self.items.insert(chat_id, item.clone());
+                    updated.push(ChatListChange::Updated { chat_id, item });
+                }
+            }
+        }
+        changes.extend(updated);
+
+        self.entries = entries;
+        Ok(changes)
+    }
+}
+
+/// Returns the chats that are in both lists but need to be moved to turn
+/// the old order into the new one.
+///
+/// Chats on a longest increasing subsequence of new positions keep their place,
+/// so moving one chat to the top only reports that chat.
+fn moved_chats(old_ids: &[u32], new_ids: &[u32]) -> HashSet<u32> {
+    let new_index: HashMap<u32, usize> = new_ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (*id, index))
+        .collect();
+    let kept: Vec<usize> = old_ids
+        .iter()
+        .filter_map(|id| new_index.get(id).copied())
+        .collect();
+
+    // tails[k] is the position in `kept` of the smallest end of an increasing subsequence of length k+1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors = vec![None; kept.len()];
+    for (i, index) in kept.iter().enumerate() {
+        let length = tails.partition_point(|&tail| kept[tail] < *index);
+        if length > 0 {
+            predecessors[i] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+    let mut staying = HashSet::new();
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        staying.insert(kept[i]);
+        next = predecessors[i];
+    }
+
+    kept.iter()
+        .filter(|index| !staying.contains(index))
+        .map(|index| new_ids[*index])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::moved_chats;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_moved_chats() {
+        assert_eq!(moved_chats(&[1, 2, 3], &[1, 2, 3]), HashSet::new());
+        assert_eq!(moved_chats(&[1, 2, 3], &[3, 1, 2]), HashSet::from([3]));
+        assert_eq!(moved_chats(&[1, 2, 3], &[4, 1, 3]), HashSet::new());
+        assert_eq!(moved_chats(&[1, 2, 3, 4], &[2, 1, 4, 3]).len(), 2);
+        assert_eq!(moved_chats(&[], &[1]), HashSet::new());
+    }
+}
@@ -0,0 +1,329 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use typescript_type_def::TypeDef;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by the json-rpc methods.
+///
+/// Every variant has its own json-rpc error code and puts its fields
+/// into the `data` of the json-rpc error, so clients can tell them apart
+/// without parsing the message.
+#[derive(Debug)]
+pub enum Error {
+    AccountNotFound {
+        account_id: u32,
+    },
+    ChatNotFound {
+        chat_id: u32,
+    },
+    ContactNotFound {
+        contact_id: u32,
+    },
+    MessageNotFound {
+        message_id: u32,
+    },
+    InvalidAddress {
+        address: String,
+    },
+    UnknownConfigKey {
+        key: String,
+    },
+    /// The message is no webxdc message, but the method needs one.
+    NotWebxdcMessage {
+        message_id: u32,
+    },
+    /// The chat is no contact request, but the method needs one.
+    NotContactRequest {
+        chat_id: u32,
+    },
+    UnknownStockString {
+        id: u32,
+    },
+    NotConfigured {
+        account_id: u32,
+    },
+    /// Another long running operation (like configure) is already running for this account.
+    IoBusy {
+        account_id: u32,
+    },
+    /// The long running operation was cancelled with `cancel_ongoing_operation`.
+    Cancelled {
+        account_id: u32,
+    },
+    /// Sending a videochat invitation needs a `webrtc_instance`, see `set_webrtc_instance`.
+    WebrtcInstanceNotSet {
+        account_id: u32,
+    },
+    /// Configuring the account failed.
+    ///
+    /// The core only reports a message, which may be translated with stock strings,
+    /// so the failed step can't be told reliably and only the reason is given.
+    ConfigureFailed {
+        account_id: u32,
+        reason: String,
+    },
+    /// Getting the OAuth2 access token with the code of `configure_with_oauth2_code` failed.
+    Oauth2Failed {
+        account_id: u32,
+        reason: String,
+    },
+    /// Any other error, reported with the generic error code.
+    Other(anyhow::Error),
+}
+
+impl Error {
+    pub const GENERIC: i32 = -1;
+    pub const ACCOUNT_NOT_FOUND: i32 = 100;
+    pub const CHAT_NOT_FOUND: i32 = 101;
+    pub const CONTACT_NOT_FOUND: i32 = 102;
+    pub const MESSAGE_NOT_FOUND: i32 = 103;
+    pub const INVALID_ADDRESS: i32 = 200;
+    pub const UNKNOWN_CONFIG_KEY: i32 = 201;
+    pub const NOT_WEBXDC_MESSAGE: i32 = 202;
+    pub const NOT_CONTACT_REQUEST: i32 = 203;
+    pub const UNKNOWN_STOCK_STRING: i32 = 204;
+    pub const NOT_CONFIGURED: i32 = 300;
+    pub const IO_BUSY: i32 = 301;
+    pub const CANCELLED: i32 = 302;
+    pub const WEBRTC_INSTANCE_NOT_SET: i32 = 303;
+    pub const CONFIGURE_FAILED: i32 = 400;
+    pub const OAUTH2_FAILED: i32 = 401;
+
+    pub fn configure_failed(account_id: u32, err: anyhow::Error) -> Self {
+        Error::ConfigureFailed {
+            account_id,
+            reason: format!("{:#}", err),
+        }
+    }
+
+    /// Turns the error of loading a database row into `not_found` if the row doesn't exist,
+    /// other errors (like a failing database) are kept.
+    pub fn or_not_found(err: anyhow::Error, not_found: Error) -> Self {
+        let missing_row = err.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<rusqlite::Error>(),
+                Some(rusqlite::Error::QueryReturnedNoRows)
+            )
+        });
+        if missing_row {
+            not_found
+        } else {
+            Error::from(err)
+        }
+    }
+
+    pub fn oauth2_failed(account_id: u32, err: anyhow::Error) -> Self {
+        Error::Oauth2Failed {
+            account_id,
+            reason: format!("{:#}", err),
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        match self {
+            Error::AccountNotFound { .. } => Self::ACCOUNT_NOT_FOUND,
+            Error::ChatNotFound { .. } => Self::CHAT_NOT_FOUND,
+            Error::ContactNotFound { .. } => Self::CONTACT_NOT_FOUND,
+            Error::MessageNotFound { .. } => Self::MESSAGE_NOT_FOUND,
+            Error::InvalidAddress { .. } => Self::INVALID_ADDRESS,
+            Error::UnknownConfigKey { .. } => Self::UNKNOWN_CONFIG_KEY,
+            Error::NotWebxdcMessage { .. } => Self::NOT_WEBXDC_MESSAGE,
+            Error::NotContactRequest { .. } => Self::NOT_CONTACT_REQUEST,
+            Error::UnknownStockString { .. } => Self::UNKNOWN_STOCK_STRING,
+            Error::NotConfigured { .. } => Self::NOT_CONFIGURED,
+            Error::IoBusy { .. } => Self::IO_BUSY,
+            Error::Cancelled { .. } => Self::CANCELLED,
+            Error::WebrtcInstanceNotSet { .. } => Self::WEBRTC_INSTANCE_NOT_SET,
+            Error::ConfigureFailed { .. } => Self::CONFIGURE_FAILED,
+            Error::Oauth2Failed { .. } => Self::OAUTH2_FAILED,
+            Error::Other(_) => Self::GENERIC,
+        }
+    }
+
+    /// Machine readable details, sent as `data` of the json-rpc error.
+    pub fn data(&self) -> Option<Value> {
+        let data = match self {
+            Error::AccountNotFound { account_id } => ErrorData::AccountNotFound {
+                account_id: *account_id,
+            },
+            Error::ChatNotFound { chat_id } => ErrorData::ChatNotFound { chat_id: *chat_id },
+            Error::ContactNotFound { contact_id } => ErrorData::ContactNotFound {
+                contact_id: *contact_id,
+            },
+            Error::MessageNotFound { message_id } => ErrorData::MessageNotFound {
+                message_id: *message_id,
+            },
+            Error::InvalidAddress { address } => ErrorData::InvalidAddress {
+                address: address.clone(),
+            },
+            Error::UnknownConfigKey { key } => ErrorData::UnknownConfigKey { key: key.clone() },
+            Error::NotWebxdcMessage { message_id } => ErrorData::NotWebxdcMessage {
+                message_id: *message_id,
+            },
+            Error::NotContactRequest { chat_id } => {
+                ErrorData::NotContactRequest { chat_id: *chat_id }
+            }
+            Error::UnknownStockString { id } => ErrorData::UnknownStockString { id: *id },
+            Error::NotConfigured { account_id } => ErrorData::NotConfigured {
+                account_id: *account_id,
+            },
+            Error::IoBusy { account_id } => ErrorData::IoBusy {
+                account_id: *account_id,
+            },
+            Error::Cancelled { account_id } => ErrorData::Cancelled {
+                account_id: *account_id,
+            },
+            Error::WebrtcInstanceNotSet { account_id } => ErrorData::WebrtcInstanceNotSet {
+                account_id: *account_id,
+            },
+            Error::ConfigureFailed { account_id, reason } => ErrorData::ConfigureFailed {
+                account_id: *account_id,
+                reason: reason.clone(),
+            },
+            Error::Oauth2Failed { account_id, reason } => ErrorData::Oauth2Failed {
+                account_id: *account_id,
+                reason: reason.clone(),
+            },
+            Error::Other(_) => return None,
+        };
+        serde_json::to_value(data).ok()
+    }
+}
+
+/// The `data` of the json-rpc errors, tells which [`Error`] it is.
+#[derive(Serialize, TypeDef)]
+#[serde(tag = "kind")]
+pub enum ErrorData {
+    #[serde(rename_all = "camelCase")]
+    AccountNotFound {
+        account_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    ChatNotFound {
+        chat_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    ContactNotFound {
+        contact_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    MessageNotFound {
+        message_id: u32,
+    },
+    InvalidAddress {
+        address: String,
+    },
+    UnknownConfigKey {
+        key: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    NotWebxdcMessage {
+        message_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    NotContactRequest {
+        chat_id: u32,
+    },
+    UnknownStockString {
+        id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    NotConfigured {
+        account_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    IoBusy {
+        account_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Cancelled {
+        account_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    WebrtcInstanceNotSet {
+        account_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    ConfigureFailed {
+        account_id: u32,
+        reason: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Oauth2Failed {
+        account_id: u32,
+        reason: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AccountNotFound { account_id } => {
+                write!(f, "account with id {} not found", account_id)
+            }
+            Error::ChatNotFound { chat_id } => write!(f, "chat with id {} not found", chat_id),
+            Error::ContactNotFound { contact_id } => {
+                write!(f, "contact with id {} not found", contact_id)
+            }
+            Error::MessageNotFound { message_id } => {
+                write!(f, "message with id {} not found", message_id)
+            }
+            Error::InvalidAddress { address } => {
+                write!(f, "{:?} is not a valid email address", address)
+            }
+            Error::UnknownConfigKey { key } => write!(f, "unknown config key: {}", key),
+            Error::NotWebxdcMessage { message_id } => {
+                write!(f, "message with id {} is no webxdc message", message_id)
+            }
+            Error::NotContactRequest { chat_id } => {
+                write!(f, "chat with id {} is no contact request", chat_id)
+            }
+            Error::UnknownStockString { id } => write!(f, "unknown stock string id {}", id),
+            Error::NotConfigured { account_id } => {
+                write!(f, "account with id {} is not configured", account_id)
+            }
+            Error::IoBusy { account_id } => write!(
+                f,
+                "account with id {} is busy with another long running operation",
+                account_id
+            ),
+            Error::Cancelled { account_id } => write!(
+                f,
+                "operation of account with id {} was cancelled",
+                account_id
+            ),
+            Error::WebrtcInstanceNotSet { account_id } => write!(
+                f,
+                "account with id {} has no webrtc_instance set, see set_webrtc_instance",
+                account_id
+            ),
+            Error::ConfigureFailed { reason, .. } => write!(f, "configure failed: {}", reason),
+            Error::Oauth2Failed { reason, .. } => write!(f, "OAuth2 login failed: {}", reason),
+            Error::Other(err) => write!(f, "{:#}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<anyhow::Error> for Error {
+    /// Keeps typed errors that were passed through `anyhow`, everything else becomes [`Error::Other`].
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<Error>() {
+            Ok(err) => err,
+            Err(err) => Error::Other(err),
+        }
+    }
+}
+
+impl From<Error> for yerpc::Error {
+    fn from(err: Error) -> Self {
+        yerpc::Error {
+            code: err.code(),
+            message: err.to_string(),
+            data: err.data(),
+        }
+    }
+}
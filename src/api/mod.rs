@@ -1,53 +1,301 @@
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, Context};
 use async_std::sync::{Arc, RwLock};
 use deltachat::{
-    chat::{get_chat_msgs, ChatId},
+    blob::BlobObject,
+    chat::{
+        add_device_msg, get_chat_media, get_chat_msgs, get_next_media, marknoticed_chat,
+        send_videochat_invitation, was_device_msg_ever_added, Chat, ChatId, Direction,
+    },
     chatlist::Chatlist,
     config::Config,
+    constants::DC_LP_AUTH_OAUTH2,
     contact::{may_be_valid_addr, Contact, ContactId},
     context::get_info,
-    message::{Message, MsgId, Viewtype},
+    message::{markseen_msgs, Message, MsgId, Viewtype},
+    oauth2::{dc_get_oauth2_access_token, dc_get_oauth2_url},
     provider::get_provider_info,
+    stock_str::StockMessage,
+    webxdc::StatusUpdateSerial,
+    Event, EventType,
 };
+use futures::future::try_join_all;
+use num_traits::FromPrimitive;
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::{collections::HashMap, str::FromStr};
+use strum::EnumProperty;
 use yerpc::rpc;
 
 pub use deltachat::accounts::Accounts;
 
+pub mod batch;
+pub mod chat_list_subscriptions;
+pub mod error;
 pub mod events;
+mod ongoing;
+pub mod server_events;
+mod stickers;
 pub mod types;
 
-use crate::api::types::chat_list::{ChatListItemFetchResult, _get_chat_list_items_by_id};
+use chat_list_subscriptions::{ChatListSubscription, ChatListSubscriptions};
+use error::{Error, Result};
+use ongoing::{OngoingGuard, OngoingOperations};
+use server_events::{Broadcast, ServerEvent, ServerEvents};
+
+use crate::api::types::chat_list::{
+    get_chat_list_entries, get_chat_list_items, ChatListItemFetchResult,
+};
 
 use types::account::Account;
-use types::chat::FullChat;
+use types::chat::{ContactRequestFetchResult, FullChat};
 use types::chat_list::ChatListEntry;
-use types::contact::ContactObject;
-use types::message::MessageObject;
+use types::config::{AccountSettings, ConfigKeyInfo, WebrtcInstance};
+use types::connectivity::Connectivity;
+use types::contact::{ContactFetchResult, ContactObject};
+use types::message::{
+    MessageFetchResult, MessageListItemFetchResult, MessageListItems, MessageObject,
+    MessageViewtype,
+};
 use types::provider_info::ProviderInfo;
+use types::webxdc::WebxdcMessageInfo;
 
 #[derive(Clone, Debug)]
 pub struct CommandApi {
     pub(crate) accounts: Arc<RwLock<Accounts>>,
+    /// Contexts that were already looked up, so getting the context of an account
+    /// does not have to wait for `accounts` while another account is added or removed.
+    ///
+    /// Entries are only inserted while holding a read lock on `accounts`
+    /// and removed while holding the write lock, so they can not go stale.
+    contexts: Arc<std::sync::RwLock<HashMap<u32, deltachat::context::Context>>>,
+    ongoing: OngoingOperations,
+    server_events: ServerEvents,
+    /// The events of all accounts, forwarded by [`CommandApi::run_event_loop`].
+    account_events: Broadcast<Event>,
+    /// Last fresh message count per account that was sent in a server event.
+    fresh_msg_counts: Arc<std::sync::Mutex<HashMap<u32, usize>>>,
+    chat_list_subscriptions: ChatListSubscriptions,
+    /// The client connection this api serves, see [`CommandApi::for_connection`].
+    connection_id: u32,
+    next_connection_id: Arc<AtomicU32>,
+    /// Stock string translations set by the client, so they can be applied to accounts added later.
+    stock_strings: Arc<std::sync::Mutex<HashMap<u32, String>>>,
 }
 
 impl CommandApi {
     pub fn new(accounts: Accounts) -> Self {
         CommandApi {
             accounts: Arc::new(RwLock::new(accounts)),
+            contexts: Default::default(),
+            ongoing: OngoingOperations::default(),
+            server_events: ServerEvents::default(),
+            account_events: Broadcast::default(),
+            fresh_msg_counts: Default::default(),
+            chat_list_subscriptions: ChatListSubscriptions::default(),
+            connection_id: 0,
+            next_connection_id: Default::default(),
+            stock_strings: Default::default(),
+        }
+    }
+
+    /// Get an api for a new client connection, it shares all state with this one.
+    ///
+    /// Chat list subscriptions belong to the connection that made them,
+    /// call [`CommandApi::close_connection`] when the connection is closed to remove them.
+    pub fn for_connection(&self) -> Self {
+        CommandApi {
+            connection_id: self.next_connection_id.fetch_add(1, Ordering::SeqCst) + 1,
+            ..self.clone()
+        }
+    }
+
+    /// Cleans up the state of the connection of this api.
+    pub fn close_connection(&self) {
+        self.chat_list_subscriptions
+            .remove_connection(self.connection_id);
+    }
+
+    /// Get a receiver for the server events of this connection emitted from now on.
+    pub fn subscribe_server_events(&self) -> async_channel::Receiver<ServerEvent> {
+        self.server_events.subscribe(self.connection_id)
+    }
+
+    /// Get a receiver for all account events received by [`CommandApi::run_event_loop`] from now on.
+    pub fn subscribe_account_events(&self) -> async_channel::Receiver<Event> {
+        self.account_events.subscribe(self.connection_id)
+    }
+
+    /// Receives the events of all accounts, updates the server state with them
+    /// and forwards them to the receivers of [`CommandApi::subscribe_account_events`].
+    ///
+    /// Has to run exactly once per server and independent of the client connections,
+    /// as every event is only handed out to one receiver of the event emitter of the accounts.
+    pub async fn run_event_loop(&self) {
+        let mut events = self.accounts.read().await.get_event_emitter().await;
+        while let Ok(Some(event)) = events.recv().await {
+            self.handle_event(&event).await;
+            self.account_events.emit(event);
+        }
+    }
+
+    /// Updates the server state for an account event and emits the resulting server events.
+    async fn handle_event(&self, event: &Event) {
+        match event.typ {
+            EventType::IncomingMsg { .. }
+            | EventType::MsgsChanged { .. }
+            | EventType::MsgsNoticed(_)
+            | EventType::ChatModified(_) => {
+                if let Err(err) = self.update_fresh_msg_count(event.id).await {
+                    log::warn!("failed to update fresh message count: {:#}", err);
+                }
+            }
+            EventType::ConfigureProgress {
+                progress,
+                ref comment,
+            } if self.ongoing.is_running(event.id) => {
+                self.server_events.emit(ServerEvent::ConfigureProgress {
+                    account_id: event.id,
+                    progress,
+                    comment: comment.clone(),
+                });
+            }
+            EventType::ConnectivityChanged => {
+                if let Ok(ctx) = self.get_context(event.id).await {
+                    self.server_events.emit(ServerEvent::ConnectivityChanged {
+                        account_id: event.id,
+                        connectivity: Connectivity::from_context(&ctx).await,
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        let changed_chat_id = match event.typ {
+            EventType::IncomingMsg { chat_id, .. }
+            | EventType::MsgsChanged { chat_id, .. }
+            | EventType::MsgDelivered { chat_id, .. }
+            | EventType::MsgFailed { chat_id, .. }
+            | EventType::MsgRead { chat_id, .. }
+            | EventType::MsgsNoticed(chat_id)
+            | EventType::ChatModified(chat_id)
+            | EventType::ChatEphemeralTimerModified { chat_id, .. } => Some(chat_id),
+            // contact names and avatars are shown in the chat list
+            EventType::ContactsChanged(_) => Some(ChatId::new(0)),
+            _ => None,
+        };
+        if let Some(chat_id) = changed_chat_id {
+            // chat id 0 means that any chat might have changed
+            let chat_id = Some(chat_id.to_u32()).filter(|id| *id != 0);
+            if let Err(err) = self.update_chat_list_subscriptions(event.id, chat_id).await {
+                log::warn!("failed to update chat list subscriptions: {:#}", err);
+            }
+        }
+    }
+
+    async fn update_chat_list_subscriptions(
+        &self,
+        account_id: u32,
+        chat_id: Option<u32>,
+    ) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        let updates = self
+            .chat_list_subscriptions
+            .update(&ctx, account_id, chat_id)
+            .await?;
+        for update in updates {
+            self.server_events.emit_to(
+                update.connection_id,
+                ServerEvent::ChatListChanged {
+                    subscription_id: update.subscription_id,
+                    account_id,
+                    changes: update.changes,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    async fn update_fresh_msg_count(&self, account_id: u32) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        let count = ctx.get_fresh_msgs().await?.len();
+        let previous = self
+            .fresh_msg_counts
+            .lock()
+            .unwrap()
+            .insert(account_id, count);
+        if previous != Some(count) {
+            self.server_events
+                .emit(ServerEvent::FreshMessageCountChanged { account_id, count });
         }
+        Ok(())
     }
 
     async fn get_context(&self, id: u32) -> Result<deltachat::context::Context> {
-        let sc = self
-            .accounts
-            .read()
-            .await
+        let cached = self.contexts.read().unwrap().get(&id).cloned();
+        if let Some(ctx) = cached {
+            return Ok(ctx);
+        }
+        let accounts = self.accounts.read().await;
+        let ctx = accounts
             .get_account(id)
             .await
-            .ok_or_else(|| anyhow!("account with id {} not found", id))?;
-        Ok(sc)
+            .ok_or(Error::AccountNotFound { account_id: id })?;
+        self.contexts.write().unwrap().insert(id, ctx.clone());
+        Ok(ctx)
+    }
+
+    /// Get the contexts of all accounts, taking the `accounts` lock only once.
+    async fn get_all_contexts(&self) -> Vec<(u32, deltachat::context::Context)> {
+        let accounts = self.accounts.read().await;
+        let mut contexts = Vec::new();
+        for id in accounts.get_all().await {
+            if let Some(ctx) = accounts.get_account(id).await {
+                contexts.push((id, ctx));
+            } else {
+                log::warn!("account with id {} doesn't exist anymore", id);
+            }
+        }
+        self.contexts
+            .write()
+            .unwrap()
+            .extend(contexts.iter().cloned());
+        contexts
+    }
+
+    /// Like `get_context`, but fails with [`Error::NotConfigured`] if the account is not configured yet.
+    async fn get_configured_context(&self, id: u32) -> Result<deltachat::context::Context> {
+        let ctx = self.get_context(id).await?;
+        if !ctx.is_configured().await? {
+            return Err(Error::NotConfigured { account_id: id });
+        }
+        Ok(ctx)
+    }
+
+    /// Runs the configure of `configure` and `configure_with_oauth2_code`,
+    /// the guard has to be taken before the credentials are set.
+    async fn run_configure(
+        &self,
+        ctx: &deltachat::context::Context,
+        account_id: u32,
+        guard: OngoingGuard,
+    ) -> Result<()> {
+        ctx.stop_io().await;
+        // a cancel that arrived while stopping IO had no core operation to stop yet
+        let result = if guard.is_cancelled() {
+            Err(Error::Cancelled { account_id })
+        } else {
+            let result = ctx
+                .configure()
+                .await
+                .map_err(|err| Error::configure_failed(account_id, err));
+            guard.check_cancelled(result)
+        };
+        // also after a failed attempt, so an account that was configured before keeps working
+        ctx.start_io().await;
+        result?;
+        self.server_events
+            .emit(ServerEvent::AccountConfigured { account_id });
+        Ok(())
     }
 }
 
@@ -67,16 +315,83 @@ impl CommandApi {
         get_info()
     }
 
+    /// Get all config keys that are meant to be set by the user,
+    /// with their type, default value and description.
+    async fn get_config_keys(&self) -> Vec<ConfigKeyInfo> {
+        types::config::get_config_keys()
+    }
+
+    /// Hint that the network is available again,
+    /// so all accounts try to connect now instead of waiting for their next retry.
+    async fn maybe_network(&self) {
+        self.accounts.read().await.maybe_network().await
+    }
+
+    /// Start fetching and sending messages for all accounts.
+    async fn start_io_for_all_accounts(&self) {
+        self.accounts.read().await.start_io().await
+    }
+
+    /// Stop fetching and sending messages for all accounts, for example while the app is in the background.
+    async fn stop_io_for_all_accounts(&self) {
+        self.accounts.read().await.stop_io().await
+    }
+
+    /// Set translations for the stock strings of the core, by stock string id,
+    /// so summaries, info messages and device messages are localized.
+    ///
+    /// The translations are set for all accounts, including the ones added later.
+    async fn set_stock_strings(&self, strings: HashMap<u32, String>) -> Result<()> {
+        // check all translations first, so an invalid one doesn't leave the accounts half translated
+        let translations = parse_stock_strings(&strings)?;
+        // hold the lock until the translations are stored,
+        // so `add_account` can't add an account that misses them
+        let accounts = self.accounts.read().await;
+        for id in accounts.get_all().await {
+            if let Some(ctx) = accounts.get_account(id).await {
+                set_stock_strings(&ctx, &translations).await?;
+            }
+        }
+        self.stock_strings.lock().unwrap().extend(strings);
+        Ok(())
+    }
+
     // ---------------------------------------------
     // Account Management
     // ---------------------------------------------
 
     async fn add_account(&self) -> Result<u32> {
-        self.accounts.write().await.add_account().await
+        let mut accounts = self.accounts.write().await;
+        let id = accounts.add_account().await?;
+        if let Some(ctx) = accounts.get_account(id).await {
+            let stock_strings = self.stock_strings.lock().unwrap().clone();
+            // the account exists already, so only warn, the translations were checked when set
+            let result = match parse_stock_strings(&stock_strings) {
+                Ok(translations) => set_stock_strings(&ctx, &translations).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                log::warn!("failed to set stock strings of account {}: {}", id, err);
+            }
+            self.contexts.write().unwrap().insert(id, ctx);
+        }
+        self.server_events
+            .emit(ServerEvent::AccountAdded { account_id: id });
+        Ok(id)
     }
 
     async fn remove_account(&self, account_id: u32) -> Result<()> {
-        self.accounts.write().await.remove_account(account_id).await
+        let mut accounts = self.accounts.write().await;
+        if accounts.get_account(account_id).await.is_none() {
+            return Err(Error::AccountNotFound { account_id });
+        }
+        self.contexts.write().unwrap().remove(&account_id);
+        self.chat_list_subscriptions.remove_account(account_id);
+        self.fresh_msg_counts.lock().unwrap().remove(&account_id);
+        accounts.remove_account(account_id).await?;
+        self.server_events
+            .emit(ServerEvent::AccountRemoved { account_id });
+        Ok(())
     }
 
     async fn get_all_account_ids(&self) -> Vec<u32> {
@@ -86,7 +401,14 @@ impl CommandApi {
     /// Select account id for internally selected state.
     /// TODO: Likely this is deprecated as all methods take an account id now.
     async fn select_account(&self, id: u32) -> Result<()> {
-        self.accounts.write().await.select_account(id).await
+        let mut accounts = self.accounts.write().await;
+        if accounts.get_account(id).await.is_none() {
+            return Err(Error::AccountNotFound { account_id: id });
+        }
+        accounts.select_account(id).await?;
+        self.server_events
+            .emit(ServerEvent::AccountSelected { account_id: id });
+        Ok(())
     }
 
     /// Get the selected account id of the internal state..
@@ -95,17 +417,51 @@ impl CommandApi {
         self.accounts.read().await.get_selected_account_id().await
     }
 
-    /// Get a list of all configured accounts.
-    async fn get_all_accounts(&self) -> Result<Vec<Account>> {
-        let mut accounts = Vec::new();
-        for id in self.accounts.read().await.get_all().await {
-            let context_option = self.accounts.read().await.get_account(id).await;
-            if let Some(ctx) = context_option {
-                accounts.push(Account::from_context(&ctx, id).await?)
-            } else {
-                println!("account with id {} doesn't exist anymore", id);
+    /// Get the number of fresh messages of every account, for example for a badge counter.
+    async fn get_fresh_msg_counts(&self) -> Result<HashMap<u32, usize>> {
+        let mut counts = HashMap::new();
+        for (id, ctx) in self.get_all_contexts().await {
+            counts.insert(id, ctx.get_fresh_msgs().await?.len());
+        }
+        Ok(counts)
+    }
+
+    /// Get the worst connectivity of all configured accounts, for a global status indicator.
+    ///
+    /// Returns null if there is no configured account.
+    async fn get_overall_connectivity(&self) -> Result<Option<Connectivity>> {
+        let mut overall = None;
+        for (_, ctx) in self.get_all_contexts().await {
+            if !ctx.is_configured().await? {
+                continue;
             }
+            let connectivity = Connectivity::from_context(&ctx).await;
+            overall = Some(overall.map_or(connectivity, |overall: Connectivity| {
+                overall.min(connectivity)
+            }));
         }
+        Ok(overall)
+    }
+
+    /// Get the ids of the fresh messages of every account, newest first.
+    async fn get_fresh_msgs_of_all_accounts(&self) -> Result<HashMap<u32, Vec<u32>>> {
+        let mut fresh_msgs = HashMap::new();
+        for (id, ctx) in self.get_all_contexts().await {
+            let msg_ids = ctx.get_fresh_msgs().await?;
+            fresh_msgs.insert(id, msg_ids.iter().map(|id| id.to_u32()).collect());
+        }
+        Ok(fresh_msgs)
+    }
+
+    /// Get a list of all configured accounts.
+    async fn get_all_accounts(&self) -> Result<Vec<Account>> {
+        let contexts = self.get_all_contexts().await;
+        let accounts = try_join_all(
+            contexts
+                .iter()
+                .map(|(id, ctx)| Account::from_context(ctx, *id)),
+        )
+        .await?;
         Ok(accounts)
     }
 
@@ -115,15 +471,60 @@ impl CommandApi {
 
     /// Get top-level info for an account.
     async fn get_account_info(&self, account_id: u32) -> Result<Account> {
-        let context_option = self.accounts.read().await.get_account(account_id).await;
-        if let Some(ctx) = context_option {
-            Ok(Account::from_context(&ctx, account_id).await?)
-        } else {
-            Err(anyhow!(
-                "account with id {} doesn't exist anymore",
-                account_id
-            ))
+        let ctx = self.get_context(account_id).await?;
+        Ok(Account::from_context(&ctx, account_id).await?)
+    }
+
+    /// Set the own avatar to the image at the given path, null removes the avatar.
+    ///
+    /// The image is copied into the blobdir and scaled down, so the file may be removed afterwards.
+    /// Returns the updated account.
+    async fn set_self_avatar(&self, account_id: u32, path: Option<String>) -> Result<Account> {
+        let ctx = self.get_context(account_id).await?;
+        ctx.set_config(Config::Selfavatar, path.as_deref()).await?;
+        ctx.emit_event(EventType::SelfavatarChanged);
+        Ok(Account::from_context(&ctx, account_id).await?)
+    }
+
+    /// Set the own avatar to an image uploaded as base64 encoded data,
+    /// the file name is used to detect the image type.
+    ///
+    /// Returns the updated account.
+    async fn set_self_avatar_from_base64(
+        &self,
+        account_id: u32,
+        file_name: String,
+        data: String,
+    ) -> Result<Account> {
+        let ctx = self.get_context(account_id).await?;
+        let data = base64::decode(data).context("avatar is not valid base64")?;
+        let blob = BlobObject::create(&ctx, &file_name, &data).await?;
+        let path = blob.to_abs_path();
+        ctx.set_config(Config::Selfavatar, Some(&path.to_string_lossy()))
+            .await?;
+        ctx.emit_event(EventType::SelfavatarChanged);
+        Ok(Account::from_context(&ctx, account_id).await?)
+    }
+
+    /// Set the own display name and status (signature) at once.
+    /// Only the values that are not null are changed, an empty string removes them.
+    ///
+    /// Returns the updated account.
+    async fn set_self_profile(
+        &self,
+        account_id: u32,
+        display_name: Option<String>,
+        status: Option<String>,
+    ) -> Result<Account> {
+        let ctx = self.get_context(account_id).await?;
+        if let Some(display_name) = display_name {
+            ctx.set_config(Config::Displayname, Some(&display_name))
+                .await?;
         }
+        if let Some(status) = status {
+            ctx.set_config(Config::Selfstatus, Some(&status)).await?;
+        }
+        Ok(Account::from_context(&ctx, account_id).await?)
     }
 
     /// Returns provider for the given domain.
@@ -150,6 +551,19 @@ impl CommandApi {
         Ok(ProviderInfo::from_dc_type(provider_info))
     }
 
+    /// Get the number of fresh messages of an account.
+    async fn get_fresh_msg_count(&self, account_id: u32) -> Result<usize> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(ctx.get_fresh_msgs().await?.len())
+    }
+
+    /// Get the ids of the fresh messages of an account, newest first.
+    async fn get_fresh_msgs(&self, account_id: u32) -> Result<Vec<u32>> {
+        let ctx = self.get_context(account_id).await?;
+        let msg_ids = ctx.get_fresh_msgs().await?;
+        Ok(msg_ids.iter().map(|id| id.to_u32()).collect())
+    }
+
     /// Checks if the context is already configured.
     async fn is_configured(&self, account_id: u32) -> Result<bool> {
         let ctx = self.get_context(account_id).await?;
@@ -162,6 +576,21 @@ impl CommandApi {
         Ok(ctx.get_info().await?)
     }
 
+    /// Get the connectivity of an account.
+    async fn get_connectivity(&self, account_id: u32) -> Result<Connectivity> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(Connectivity::from_context(&ctx).await)
+    }
+
+    /// Get a detailed connectivity report of an account as html page.
+    ///
+    /// The report also shows the storage quota of the server. There is no typed quota method,
+    /// as the core keeps the quota internal and only puts it into this report.
+    async fn get_connectivity_html(&self, account_id: u32) -> Result<String> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(ctx.get_connectivity_html().await?)
+    }
+
     async fn set_config(&self, account_id: u32, key: String, value: Option<String>) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
         set_config(&ctx, &key, value.as_deref()).await
@@ -199,14 +628,96 @@ impl CommandApi {
         Ok(result)
     }
 
+    /// Get the typed account settings.
+    async fn get_account_settings(&self, account_id: u32) -> Result<AccountSettings> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(AccountSettings::from_context(&ctx).await?)
+    }
+
+    /// Change the typed account settings.
+    /// Only the fields that are set are changed.
+    async fn set_account_settings(&self, account_id: u32, settings: AccountSettings) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(settings.apply(&ctx).await?)
+    }
+
+    /// Get the videochat instance used for videochat invitations, if one is set.
+    async fn get_webrtc_instance(&self, account_id: u32) -> Result<Option<WebrtcInstance>> {
+        let ctx = self.get_context(account_id).await?;
+        let instance = ctx.get_config(Config::WebrtcInstance).await?;
+        Ok(instance
+            .filter(|instance| !instance.is_empty())
+            .map(|instance| WebrtcInstance::from_config(&instance)))
+    }
+
+    /// Set the videochat instance used for videochat invitations, null removes it.
+    async fn set_webrtc_instance(
+        &self,
+        account_id: u32,
+        instance: Option<WebrtcInstance>,
+    ) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        let value = match instance {
+            Some(instance) => Some(instance.to_config()?),
+            None => None,
+        };
+        Ok(ctx
+            .set_config(Config::WebrtcInstance, value.as_deref())
+            .await?)
+    }
+
     /// Configures this account with the currently set parameters.
     /// Setup the credential config before calling this.
     async fn configure(&self, account_id: u32) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
-        ctx.stop_io().await;
-        ctx.configure().await?;
-        ctx.start_io().await;
-        Ok(())
+        let guard = self.ongoing.start(account_id)?;
+        // the password is used, also if the account was configured with OAuth2 before
+        set_oauth2_flag(&ctx, false).await?;
+        self.run_configure(&ctx, account_id, guard).await
+    }
+
+    /// Get the url to log in at the email provider of the address using OAuth2.
+    ///
+    /// After logging in, the provider redirects to `redirect_uri` with the code
+    /// that has to be passed to `configure_with_oauth2_code`.
+    /// Returns null if the provider does not support OAuth2.
+    async fn get_oauth2_url(
+        &self,
+        account_id: u32,
+        email: String,
+        redirect_uri: String,
+    ) -> Result<Option<String>> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(dc_get_oauth2_url(&ctx, &email, &redirect_uri).await?)
+    }
+
+    /// Configures this account with the code that the OAuth2 login redirected to.
+    ///
+    /// Fails with `Oauth2Failed` if no access token can be got with the code.
+    async fn configure_with_oauth2_code(
+        &self,
+        account_id: u32,
+        email: String,
+        code: String,
+    ) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        let guard = self.ongoing.start(account_id)?;
+        ctx.set_config(Config::Addr, Some(&email)).await?;
+        ctx.set_config(Config::MailPw, Some(&code)).await?;
+        set_oauth2_flag(&ctx, true).await?;
+        // get the token before configuring, so a failed OAuth2 login
+        // is reported as such and not as a failed login to the server
+        match dc_get_oauth2_access_token(&ctx, &email, &code, false).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return Err(Error::Oauth2Failed {
+                    account_id,
+                    reason: "the provider returned no access token".to_owned(),
+                })
+            }
+            Err(err) => return Err(Error::oauth2_failed(account_id, err)),
+        }
+        self.run_configure(&ctx, account_id, guard).await
     }
 
     /// Signal an ongoing process to stop.
@@ -216,13 +727,45 @@ impl CommandApi {
         Ok(())
     }
 
+    /// Cancel the long running operation (like `configure`) of this account.
+    ///
+    /// Cancelling works per account, not per json-rpc request,
+    /// but as an account only runs one such operation at a time,
+    /// the account id identifies the request to cancel.
+    ///
+    /// The call that started the operation fails with a `Cancelled` error
+    /// once the operation has stopped.
+    /// Returns false if there was no operation to cancel.
+    async fn cancel_ongoing_operation(&self, account_id: u32) -> Result<bool> {
+        let ctx = self.get_context(account_id).await?;
+        if !self.ongoing.cancel(account_id) {
+            return Ok(false);
+        }
+        ctx.stop_ongoing().await;
+        Ok(true)
+    }
+
+    /// Start fetching and sending messages for this account.
+    async fn start_io(&self, account_id: u32) -> Result<()> {
+        let ctx = self.get_configured_context(account_id).await?;
+        ctx.start_io().await;
+        Ok(())
+    }
+
+    /// Stop fetching and sending messages for this account.
+    async fn stop_io(&self, account_id: u32) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        ctx.stop_io().await;
+        Ok(())
+    }
+
     // ---------------------------------------------
     //  autocrypt
     // ---------------------------------------------
 
     async fn autocrypt_initiate_key_transfer(&self, account_id: u32) -> Result<String> {
-        let ctx = self.get_context(account_id).await?;
-        deltachat::imex::initiate_key_transfer(&ctx).await
+        let ctx = self.get_configured_context(account_id).await?;
+        Ok(deltachat::imex::initiate_key_transfer(&ctx).await?)
     }
 
     async fn autocrypt_continue_key_transfer(
@@ -232,7 +775,10 @@ impl CommandApi {
         setup_code: String,
     ) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
-        deltachat::imex::continue_key_transfer(&ctx, MsgId::new(message_id), &setup_code).await
+        Ok(
+            deltachat::imex::continue_key_transfer(&ctx, MsgId::new(message_id), &setup_code)
+                .await?,
+        )
     }
 
     // ---------------------------------------------
@@ -247,21 +793,10 @@ impl CommandApi {
         query_contact_id: Option<u32>,
     ) -> Result<Vec<ChatListEntry>> {
         let ctx = self.get_context(account_id).await?;
-        let list = Chatlist::try_load(
-            &ctx,
-            list_flags.unwrap_or(0) as usize,
-            query_string.as_deref(),
-            query_contact_id.map(ContactId::new),
+        Ok(
+            get_chat_list_entries(&ctx, list_flags, query_string.as_deref(), query_contact_id)
+                .await?,
         )
-        .await?;
-        let mut l: Vec<ChatListEntry> = Vec::new();
-        for i in 0..list.len() {
-            l.push(ChatListEntry(
-                list.get_chat_id(i)?.to_u32(),
-                list.get_msg_id(i)?.unwrap_or_default().to_u32(),
-            ));
-        }
-        Ok(l)
     }
 
     async fn get_chatlist_items_by_entries(
@@ -271,20 +806,42 @@ impl CommandApi {
     ) -> Result<HashMap<u32, ChatListItemFetchResult>> {
         // todo custom json deserializer for ChatListEntry?
         let ctx = self.get_context(account_id).await?;
-        let mut result: HashMap<u32, ChatListItemFetchResult> = HashMap::new();
-        for (_i, entry) in entries.iter().enumerate() {
-            result.insert(
-                entry.0,
-                match _get_chat_list_items_by_id(&ctx, entry).await {
-                    Ok(res) => res,
-                    Err(err) => ChatListItemFetchResult::Error {
-                        id: entry.0,
-                        error: format!("{:?}", err),
-                    },
-                },
-            );
-        }
-        Ok(result)
+        Ok(get_chat_list_items(&ctx, &entries).await)
+    }
+
+    /// Subscribe to a chat list, to get its changes pushed
+    /// in `ChatListChanged` server events instead of reloading it after every event.
+    ///
+    /// The arguments are the same as for `get_chatlist_entries`.
+    /// Returns the subscription id and the current state of the chat list.
+    ///
+    /// The subscription is removed when the connection that made it is closed.
+    async fn chatlist_subscribe(
+        &self,
+        account_id: u32,
+        list_flags: Option<u32>,
+        query_string: Option<String>,
+        query_contact_id: Option<u32>,
+    ) -> Result<ChatListSubscription> {
+        let ctx = self.get_context(account_id).await?;
+        self.chat_list_subscriptions
+            .subscribe(
+                &ctx,
+                self.connection_id,
+                account_id,
+                list_flags,
+                query_string,
+                query_contact_id,
+            )
+            .await
+    }
+
+    /// Stop getting changes of the chat list.
+    ///
+    /// Returns false if this connection has no subscription with this id.
+    async fn chatlist_unsubscribe(&self, subscription_id: u32) -> bool {
+        self.chat_list_subscriptions
+            .unsubscribe(self.connection_id, subscription_id)
     }
 
     // ---------------------------------------------
@@ -297,17 +854,116 @@ impl CommandApi {
         chat_id: u32,
     ) -> Result<FullChat> {
         let ctx = self.get_context(account_id).await?;
-        FullChat::from_dc_chat_id(&ctx, chat_id).await
+        Ok(FullChat::from_dc_chat_id(&ctx, chat_id).await?)
     }
 
     async fn accept_chat(&self, account_id: u32, chat_id: u32) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
-        ChatId::new(chat_id).accept(&ctx).await
+        Ok(ChatId::new(chat_id).accept(&ctx).await?)
     }
 
     async fn block_chat(&self, account_id: u32, chat_id: u32) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
-        ChatId::new(chat_id).block(&ctx).await
+        Ok(ChatId::new(chat_id).block(&ctx).await?)
+    }
+
+    /// Get the pending contact requests, newest first.
+    ///
+    /// Accept them with `accept_chat` or block them with `block_chat`.
+    /// A request that can't be loaded is returned as error item instead of failing the whole list.
+    async fn get_contact_requests(
+        &self,
+        account_id: u32,
+    ) -> Result<Vec<ContactRequestFetchResult>> {
+        let ctx = self.get_context(account_id).await?;
+        let list = Chatlist::try_load(&ctx, 0, None, None).await?;
+        let mut requests = Vec::new();
+        for i in 0..list.len() {
+            let chat_id = list.get_chat_id(i)?;
+            if chat_id.is_special() {
+                continue;
+            }
+            if let Some(request) = ContactRequestFetchResult::from_dc_chat_id(&ctx, chat_id).await {
+                requests.push(request);
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Declines a contact request without blocking the requester
+    /// by deleting the chat. A new message of the requester starts a new request.
+    async fn decline_contact_request(&self, account_id: u32, chat_id: u32) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        let chat = Chat::load_from_db(&ctx, ChatId::new(chat_id))
+            .await
+            .map_err(|err| Error::or_not_found(err, Error::ChatNotFound { chat_id }))?;
+        if !chat.is_contact_request() {
+            return Err(Error::NotContactRequest { chat_id });
+        }
+        Ok(ChatId::new(chat_id).delete(&ctx).await?)
+    }
+
+    /// Marks all messages of the chat as noticed, so they are not fresh anymore.
+    ///
+    /// In contrast to `markseen_msgs` no read receipts are sent.
+    async fn marknoticed_chat(&self, account_id: u32, chat_id: u32) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(marknoticed_chat(&ctx, ChatId::new(chat_id)).await?)
+    }
+
+    /// Send a videochat invitation to the chat, using the instance set with `set_webrtc_instance`.
+    ///
+    /// Returns the created message.
+    async fn send_videochat_invitation(
+        &self,
+        account_id: u32,
+        chat_id: u32,
+    ) -> Result<MessageObject> {
+        let ctx = self.get_configured_context(account_id).await?;
+        if ctx
+            .get_config(Config::WebrtcInstance)
+            .await?
+            .unwrap_or_default()
+            .is_empty()
+        {
+            return Err(Error::WebrtcInstanceNotSet { account_id });
+        }
+        let message_id = send_videochat_invitation(&ctx, ChatId::new(chat_id)).await?;
+        Ok(MessageObject::from_message_id(&ctx, message_id.to_u32()).await?)
+    }
+
+    /// Add a message to the device chat, with an optional attachment.
+    ///
+    /// A message with a `label` is only added once, even if the device chat was deleted meanwhile.
+    /// Returns the id of the added message or null if the label was already used.
+    async fn add_device_message(
+        &self,
+        account_id: u32,
+        label: Option<String>,
+        text: Option<String>,
+        file: Option<String>,
+    ) -> Result<Option<u32>> {
+        let ctx = self.get_context(account_id).await?;
+        let mut msg = match file {
+            Some(file) => {
+                // the core guesses the actual viewtype from the file
+                let mut msg = Message::new(Viewtype::File);
+                msg.set_file(file, None);
+                msg
+            }
+            None => Message::new(Viewtype::Text),
+        };
+        msg.set_text(text);
+        let message_id = add_device_msg(&ctx, label.as_deref(), Some(&mut msg)).await?;
+        Ok(Some(message_id)
+            .filter(|id| !id.is_unset())
+            .map(|id| id.to_u32()))
+    }
+
+    /// Check if a device message with this label was already added.
+    async fn was_device_message_ever_added(&self, account_id: u32, label: String) -> Result<bool> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(was_device_msg_ever_added(&ctx, &label).await?)
     }
 
     // ---------------------------------------------
@@ -333,25 +989,128 @@ impl CommandApi {
 
     async fn message_get_message(&self, account_id: u32, message_id: u32) -> Result<MessageObject> {
         let ctx = self.get_context(account_id).await?;
-        MessageObject::from_message_id(&ctx, message_id).await
+        Ok(MessageObject::from_message_id(&ctx, message_id).await?)
     }
 
     async fn message_get_messages(
         &self,
         account_id: u32,
         message_ids: Vec<u32>,
-    ) -> Result<HashMap<u32, MessageObject>> {
+    ) -> Result<HashMap<u32, MessageFetchResult>> {
         let ctx = self.get_context(account_id).await?;
-        let mut messages: HashMap<u32, MessageObject> = HashMap::new();
+        let mut messages: HashMap<u32, MessageFetchResult> = HashMap::new();
         for message_id in message_ids {
             messages.insert(
                 message_id,
-                MessageObject::from_message_id(&ctx, message_id).await?,
+                MessageFetchResult::from_message_id(&ctx, message_id).await,
             );
         }
         Ok(messages)
     }
 
+    /// Get the messages for rendering a message list, see `MessageListItem`.
+    ///
+    /// Their senders are loaded only once and returned in `contacts`.
+    async fn message_list_get_items(
+        &self,
+        account_id: u32,
+        message_ids: Vec<u32>,
+    ) -> Result<MessageListItems> {
+        let ctx = self.get_context(account_id).await?;
+        let mut messages = HashMap::new();
+        let mut contacts = HashMap::new();
+        for message_id in message_ids {
+            let message = MessageListItemFetchResult::from_message_id(&ctx, message_id).await;
+            if let Some(from_id) = message.from_id() {
+                if !contacts.contains_key(&from_id) {
+                    contacts.insert(
+                        from_id,
+                        ContactFetchResult::from_contact_id(&ctx, from_id).await,
+                    );
+                }
+            }
+            messages.insert(message_id, message);
+        }
+        Ok(MessageListItems { messages, contacts })
+    }
+
+    /// Marks the messages as seen, call this when they are shown to the user.
+    ///
+    /// This sends read receipts to the senders if enabled
+    /// and emits `MsgsNoticed` so the fresh message counters update.
+    async fn markseen_msgs(&self, account_id: u32, message_ids: Vec<u32>) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        let msg_ids = message_ids.into_iter().map(MsgId::new).collect();
+        Ok(markseen_msgs(&ctx, msg_ids).await?)
+    }
+
+    /// Download the rest of a partially downloaded message, see `download_state`.
+    ///
+    /// This only starts the download, a `MsgsChanged` event is emitted when it is done.
+    async fn download_full_message(&self, account_id: u32, message_id: u32) -> Result<()> {
+        let ctx = self.get_configured_context(account_id).await?;
+        load_message(&ctx, message_id).await?;
+        Ok(MsgId::new(message_id).download_full(&ctx).await?)
+    }
+
+    /// Get the ids of all messages with one of the given viewtypes (at most three),
+    /// oldest first.
+    ///
+    /// If `chat_id` is null, the media of all chats is returned.
+    async fn chat_get_media(
+        &self,
+        account_id: u32,
+        chat_id: Option<u32>,
+        message_types: Vec<MessageViewtype>,
+    ) -> Result<Vec<u32>> {
+        let ctx = self.get_context(account_id).await?;
+        let (msg_type, msg_type2, msg_type3) = media_types(message_types)?;
+        let media = get_chat_media(
+            &ctx,
+            chat_id.map(ChatId::new),
+            msg_type,
+            msg_type2,
+            msg_type3,
+        )
+        .await?;
+        Ok(media.iter().map(|msg_id| msg_id.to_u32()).collect())
+    }
+
+    /// Get the ids of the previous and of the next message with one of the
+    /// given viewtypes (at most three) in the chat of the message,
+    /// to navigate through the media of a chat.
+    async fn chat_get_neighboring_media(
+        &self,
+        account_id: u32,
+        message_id: u32,
+        message_types: Vec<MessageViewtype>,
+    ) -> Result<(Option<u32>, Option<u32>)> {
+        let ctx = self.get_context(account_id).await?;
+        let (msg_type, msg_type2, msg_type3) = media_types(message_types)?;
+        let msg_id = MsgId::new(message_id);
+        let prev = get_next_media(
+            &ctx,
+            msg_id,
+            Direction::Backward,
+            msg_type,
+            msg_type2,
+            msg_type3,
+        )
+        .await?
+        .map(|id| id.to_u32());
+        let next = get_next_media(
+            &ctx,
+            msg_id,
+            Direction::Forward,
+            msg_type,
+            msg_type2,
+            msg_type3,
+        )
+        .await?
+        .map(|id| id.to_u32());
+        Ok((prev, next))
+    }
+
     // ---------------------------------------------
     //  contact
     // ---------------------------------------------
@@ -363,13 +1122,9 @@ impl CommandApi {
         contact_id: u32,
     ) -> Result<ContactObject> {
         let ctx = self.get_context(account_id).await?;
-        let contact_id = ContactId::new(contact_id);
+        let contact = load_contact(&ctx, contact_id).await?;
 
-        ContactObject::from_dc_contact(
-            &ctx,
-            deltachat::contact::Contact::get_by_id(&ctx, contact_id).await?,
-        )
-        .await
+        Ok(ContactObject::from_dc_contact(&ctx, contact).await?)
     }
 
     /// Add a single contact as a result of an explicit user action.
@@ -383,9 +1138,7 @@ impl CommandApi {
     ) -> Result<u32> {
         let ctx = self.get_context(account_id).await?;
         if !may_be_valid_addr(&email) {
-            bail!(anyhow!(
-                "provided email address is not a valid email address"
-            ))
+            return Err(Error::InvalidAddress { address: email });
         }
         let contact_id = Contact::create(&ctx, &name.unwrap_or_default(), &email).await?;
         Ok(contact_id.to_u32())
@@ -398,34 +1151,28 @@ impl CommandApi {
         contact_id: u32,
     ) -> Result<u32> {
         let ctx = self.get_context(account_id).await?;
-        let contact = Contact::get_by_id(&ctx, ContactId::new(contact_id)).await?;
-        ChatId::create_for_contact(&ctx, contact.id)
+        let contact = load_contact(&ctx, contact_id).await?;
+        Ok(ChatId::create_for_contact(&ctx, contact.id)
             .await
-            .map(|id| id.to_u32())
+            .map(|id| id.to_u32())?)
     }
 
     async fn contacts_block(&self, account_id: u32, contact_id: u32) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
-        Contact::block(&ctx, ContactId::new(contact_id)).await
+        Ok(Contact::block(&ctx, ContactId::new(contact_id)).await?)
     }
 
     async fn contacts_unblock(&self, account_id: u32, contact_id: u32) -> Result<()> {
         let ctx = self.get_context(account_id).await?;
-        Contact::unblock(&ctx, ContactId::new(contact_id)).await
+        Ok(Contact::unblock(&ctx, ContactId::new(contact_id)).await?)
     }
 
-    async fn contacts_get_blocked(&self, account_id: u32) -> Result<Vec<ContactObject>> {
+    async fn contacts_get_blocked(&self, account_id: u32) -> Result<Vec<ContactFetchResult>> {
         let ctx = self.get_context(account_id).await?;
         let blocked_ids = Contact::get_all_blocked(&ctx).await?;
-        let mut contacts: Vec<ContactObject> = Vec::with_capacity(blocked_ids.len());
+        let mut contacts: Vec<ContactFetchResult> = Vec::with_capacity(blocked_ids.len());
         for id in blocked_ids {
-            contacts.push(
-                ContactObject::from_dc_contact(
-                    &ctx,
-                    deltachat::contact::Contact::get_by_id(&ctx, id).await?,
-                )
-                .await?,
-            );
+            contacts.push(ContactFetchResult::from_contact_id(&ctx, id.to_u32()).await);
         }
         Ok(contacts)
     }
@@ -448,18 +1195,12 @@ impl CommandApi {
         account_id: u32,
         list_flags: u32,
         query: Option<String>,
-    ) -> Result<Vec<ContactObject>> {
+    ) -> Result<Vec<ContactFetchResult>> {
         let ctx = self.get_context(account_id).await?;
         let contact_ids = Contact::get_all(&ctx, list_flags, query).await?;
-        let mut contacts: Vec<ContactObject> = Vec::with_capacity(contact_ids.len());
+        let mut contacts: Vec<ContactFetchResult> = Vec::with_capacity(contact_ids.len());
         for id in contact_ids {
-            contacts.push(
-                ContactObject::from_dc_contact(
-                    &ctx,
-                    deltachat::contact::Contact::get_by_id(&ctx, id).await?,
-                )
-                .await?,
-            );
+            contacts.push(ContactFetchResult::from_contact_id(&ctx, id.to_u32()).await);
         }
         Ok(contacts)
     }
@@ -468,23 +1209,123 @@ impl CommandApi {
         &self,
         account_id: u32,
         ids: Vec<u32>,
-    ) -> Result<HashMap<u32, ContactObject>> {
+    ) -> Result<HashMap<u32, ContactFetchResult>> {
         let ctx = self.get_context(account_id).await?;
 
         let mut contacts = HashMap::with_capacity(ids.len());
         for id in ids {
-            contacts.insert(
-                id,
-                ContactObject::from_dc_contact(
-                    &ctx,
-                    deltachat::contact::Contact::get_by_id(&ctx, ContactId::new(id)).await?,
-                )
-                .await?,
-            );
+            contacts.insert(id, ContactFetchResult::from_contact_id(&ctx, id).await);
         }
         Ok(contacts)
     }
 
+    // ---------------------------------------------
+    //  webxdc
+    // ---------------------------------------------
+
+    /// Send a status update to all instances of the webxdc app, including this one.
+    ///
+    /// `description` is shown in the chat, it may be empty.
+    async fn webxdc_send_status_update(
+        &self,
+        account_id: u32,
+        instance_msg_id: u32,
+        update_str: String,
+        description: String,
+    ) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        ctx.send_webxdc_status_update(MsgId::new(instance_msg_id), &update_str, &description)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the status updates of the webxdc app with a serial greater than `last_known_serial`,
+    /// as json array string.
+    ///
+    /// Pass 0 to get all updates. Call this after a `WEBXDC_STATUS_UPDATE` event
+    /// with the last serial that was received.
+    async fn webxdc_get_status_updates(
+        &self,
+        account_id: u32,
+        instance_msg_id: u32,
+        last_known_serial: u32,
+    ) -> Result<String> {
+        let ctx = self.get_context(account_id).await?;
+        load_webxdc_message(&ctx, instance_msg_id).await?;
+        Ok(ctx
+            .get_webxdc_status_updates(
+                MsgId::new(instance_msg_id),
+                StatusUpdateSerial::new(last_known_serial),
+            )
+            .await?)
+    }
+
+    /// Get name, icon and summary of a webxdc message.
+    async fn message_get_webxdc_info(
+        &self,
+        account_id: u32,
+        instance_msg_id: u32,
+    ) -> Result<WebxdcMessageInfo> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(WebxdcMessageInfo::from_message_id(&ctx, instance_msg_id).await?)
+    }
+
+    /// Get a file from the .xdc archive of a webxdc message, base64 encoded.
+    ///
+    /// `path` is relative to the root of the archive, like `index.html`.
+    async fn webxdc_get_blob(
+        &self,
+        account_id: u32,
+        instance_msg_id: u32,
+        path: String,
+    ) -> Result<String> {
+        let ctx = self.get_context(account_id).await?;
+        let message = load_webxdc_message(&ctx, instance_msg_id).await?;
+        let blob = message.get_webxdc_blob(&ctx, &path).await?;
+        Ok(base64::encode(blob))
+    }
+
+    // ---------------------------------------------
+    //  stickers
+    // ---------------------------------------------
+
+    /// Get the stickers of the account: the paths of the sticker images by pack name.
+    async fn stickers_get_stickers(&self, account_id: u32) -> Result<HashMap<String, Vec<String>>> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(stickers::get_stickers(&ctx).await?)
+    }
+
+    /// Copy an image into a sticker pack of the account, the pack is created if needed.
+    ///
+    /// Returns the path of the new sticker.
+    async fn stickers_import_sticker(
+        &self,
+        account_id: u32,
+        pack: String,
+        path: String,
+    ) -> Result<String> {
+        let ctx = self.get_context(account_id).await?;
+        Ok(stickers::import_sticker(&ctx, &pack, &path).await?)
+    }
+
+    /// Send a sticker, for example one returned by `stickers_get_stickers`.
+    ///
+    /// Returns the id of the sent message.
+    async fn send_sticker(
+        &self,
+        account_id: u32,
+        chat_id: u32,
+        sticker_path: String,
+    ) -> Result<u32> {
+        let ctx = self.get_configured_context(account_id).await?;
+
+        let mut msg = Message::new(Viewtype::Sticker);
+        msg.set_file(sticker_path, None);
+
+        let message_id = deltachat::chat::send_msg(&ctx, ChatId::new(chat_id), &mut msg).await?;
+        Ok(message_id.to_u32())
+    }
+
     // ---------------------------------------------
     //           misc prototyping functions
     //       that might get removed later again
@@ -497,7 +1338,7 @@ impl CommandApi {
         text: String,
         chat_id: u32,
     ) -> Result<u32> {
-        let ctx = self.get_context(account_id).await?;
+        let ctx = self.get_configured_context(account_id).await?;
 
         let mut msg = Message::new(Viewtype::Text);
         msg.set_text(Some(text));
@@ -512,23 +1353,127 @@ async fn set_config(
     ctx: &deltachat::context::Context,
     key: &str,
     value: Option<&str>,
-) -> Result<(), anyhow::Error> {
+) -> Result<()> {
     if key.starts_with("ui.") {
-        ctx.set_ui_config(key, value).await
+        ctx.set_ui_config(key, value).await?;
     } else {
-        ctx.set_config(Config::from_str(key).context("unknown key")?, value)
-            .await
+        ctx.set_config(parse_config_key(key)?, value).await?;
     }
+    Ok(())
 }
 
-async fn get_config(
-    ctx: &deltachat::context::Context,
-    key: &str,
-) -> Result<Option<String>, anyhow::Error> {
+async fn get_config(ctx: &deltachat::context::Context, key: &str) -> Result<Option<String>> {
     if key.starts_with("ui.") {
-        ctx.get_ui_config(key).await
+        Ok(ctx.get_ui_config(key).await?)
     } else {
-        ctx.get_config(Config::from_str(key).context("unknown key")?)
+        Ok(ctx.get_config(parse_config_key(key)?).await?)
+    }
+}
+
+/// Converts the ids of the stock string translations, fails if one of them is unknown
+/// or if a translation would be rejected by the core.
+fn parse_stock_strings(strings: &HashMap<u32, String>) -> Result<Vec<(StockMessage, &str)>> {
+    strings
+        .iter()
+        .map(|(id, translation)| {
+            let stock_message =
+                StockMessage::from_u32(*id).ok_or(Error::UnknownStockString { id: *id })?;
+            check_stock_string_placeholders(stock_message, translation)?;
+            Ok((stock_message, translation.as_str()))
+        })
+        .collect()
+}
+
+/// Does the same check as `Context::set_stock_translation`:
+/// a translation must not use placeholders that the untranslated string doesn't have.
+fn check_stock_string_placeholders(stock_message: StockMessage, translation: &str) -> Result<()> {
+    let fallback = stock_message.get_str("fallback").unwrap_or_default();
+    for placeholder in ["%1", "%2"] {
+        if translation.contains(placeholder) && !fallback.contains(placeholder) {
+            return Err(anyhow!(
+                "translation {:?} of stock string {:?} contains invalid {} placeholder, default is {:?}",
+                translation,
+                stock_message,
+                placeholder,
+                fallback
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+async fn set_stock_strings(
+    ctx: &deltachat::context::Context,
+    translations: &[(StockMessage, &str)],
+) -> Result<()> {
+    for (stock_message, translation) in translations {
+        ctx.set_stock_translation(*stock_message, translation.to_string())
             .await
+            .with_context(|| format!("Can't set stock string {:?}", stock_message))?;
+    }
+    Ok(())
+}
+
+/// Sets or clears the OAuth2 flag of the `ServerFlags`, keeping the other flags.
+async fn set_oauth2_flag(ctx: &deltachat::context::Context, oauth2: bool) -> Result<()> {
+    let server_flags = ctx.get_config_int(Config::ServerFlags).await?;
+    let new_flags = if oauth2 {
+        server_flags | DC_LP_AUTH_OAUTH2
+    } else {
+        server_flags & !DC_LP_AUTH_OAUTH2
+    };
+    if new_flags != server_flags {
+        ctx.set_config(Config::ServerFlags, Some(&new_flags.to_string()))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Converts the viewtypes to the three viewtypes the core media functions take,
+/// unused ones are set to `Viewtype::Unknown`.
+fn media_types(message_types: Vec<MessageViewtype>) -> Result<(Viewtype, Viewtype, Viewtype)> {
+    if message_types.is_empty() || message_types.len() > 3 {
+        return Err(anyhow!(
+            "between one and three message types are required, got {}",
+            message_types.len()
+        )
+        .into());
     }
+    let mut types = message_types.into_iter().map(Viewtype::from);
+    Ok((
+        types.next().unwrap_or(Viewtype::Unknown),
+        types.next().unwrap_or(Viewtype::Unknown),
+        types.next().unwrap_or(Viewtype::Unknown),
+    ))
+}
+
+fn parse_config_key(key: &str) -> Result<Config> {
+    Config::from_str(key).map_err(|_| Error::UnknownConfigKey {
+        key: key.to_owned(),
+    })
+}
+
+async fn load_message(ctx: &deltachat::context::Context, message_id: u32) -> Result<Message> {
+    Message::load_from_db(ctx, MsgId::new(message_id))
+        .await
+        .map_err(|err| Error::or_not_found(err, Error::MessageNotFound { message_id }))
+}
+
+/// Like `load_message`, but fails if the message is no webxdc message.
+async fn load_webxdc_message(
+    ctx: &deltachat::context::Context,
+    message_id: u32,
+) -> Result<Message> {
+    let message = load_message(ctx, message_id).await?;
+    if message.get_viewtype() != Viewtype::Webxdc {
+        return Err(Error::NotWebxdcMessage { message_id });
+    }
+    Ok(message)
+}
+
+async fn load_contact(ctx: &deltachat::context::Context, contact_id: u32) -> Result<Contact> {
+    Contact::get_by_id(ctx, ContactId::new(contact_id))
+        .await
+        .map_err(|err| Error::or_not_found(err, Error::ContactNotFound { contact_id }))
 }
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::error::{Error, Result};
+
+/// Keeps track of the accounts that currently run a long running operation
+/// (like configure), so a second one is rejected with [`Error::IoBusy`]
+/// instead of interfering with the first one.
+///
+/// As there is at most one operation per account, the account id is enough
+/// to cancel a specific operation.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OngoingOperations {
+    accounts: Arc<Mutex<HashMap<u32, Arc<AtomicBool>>>>,
+}
+
+impl OngoingOperations {
+    /// Marks the account as busy until the returned guard is dropped.
+    pub fn start(&self, account_id: u32) -> Result<OngoingGuard> {
+        let mut accounts = self.accounts.lock().unwrap();
+        if accounts.contains_key(&account_id) {
+            return Err(Error::IoBusy { account_id });
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        accounts.insert(account_id, cancelled.clone());
+        Ok(OngoingGuard {
+            accounts: self.accounts.clone(),
+            account_id,
+            cancelled,
+        })
+    }
+
+    pub fn is_running(&self, account_id: u32) -> bool {
+        self.accounts.lock().unwrap().contains_key(&account_id)
+    }
+
+    /// Marks the operation of the account as cancelled.
+    ///
+    /// Returns false if there is no operation running for the account.
+    pub fn cancel(&self, account_id: u32) -> bool {
+        match self.accounts.lock().unwrap().get(&account_id) {
+            Some(cancelled) => {
+                cancelled.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub(crate) struct OngoingGuard {
+    accounts: Arc<Mutex<HashMap<u32, Arc<AtomicBool>>>>,
+    account_id: u32,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl OngoingGuard {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Turns the error of a cancelled operation into [`Error::Cancelled`].
+    pub fn check_cancelled<T>(&self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(_) if self.is_cancelled() => Err(Error::Cancelled {
+                account_id: self.account_id,
+            }),
+            result => result,
+        }
+    }
+}
+
+impl Drop for OngoingGuard {
+    fn drop(&mut self) {
+        self.accounts.lock().unwrap().remove(&self.account_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OngoingOperations;
+    use crate::api::error::Error;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_second_start_is_busy() {
+        let ongoing = OngoingOperations::default();
+        let _guard = ongoing.start(1).unwrap();
+        assert!(matches!(
+            ongoing.start(1),
+            Err(Error::IoBusy { account_id: 1 })
+        ));
+        // other accounts are not affected
+        assert!(ongoing.start(2).is_ok());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let ongoing = OngoingOperations::default();
+        assert!(!ongoing.cancel(1));
+
+        let guard = ongoing.start(1).unwrap();
+        assert!(guard.check_cancelled(Ok(())).is_ok());
+        assert!(matches!(
+            guard.check_cancelled::<()>(Err(anyhow!("failed").into())),
+            Err(Error::Other(_))
+        ));
+
+        assert!(ongoing.cancel(1));
+        assert!(guard.is_cancelled());
+        assert!(matches!(
+            guard.check_cancelled::<()>(Err(anyhow!("failed").into())),
+            Err(Error::Cancelled { account_id: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_drop_guard_frees_account() {
+        let ongoing = OngoingOperations::default();
+        let guard = ongoing.start(1).unwrap();
+        assert!(ongoing.is_running(1));
+        drop(guard);
+        assert!(!ongoing.is_running(1));
+        assert!(!ongoing.cancel(1));
+        assert!(ongoing.start(1).is_ok());
+    }
+}
@@ -0,0 +1,111 @@
+use async_channel::{unbounded, Receiver, Sender};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use typescript_type_def::TypeDef;
+
+use super::chat_list_subscriptions::ChatListChange;
+use super::types::connectivity::Connectivity;
+
+/// Events of the json-rpc server itself, in contrast to the events of a single account.
+///
+/// They are sent to the connected clients as `server_event` notification,
+/// `ChatListChanged` only to the client that made the subscription.
+#[derive(Serialize, TypeDef, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum ServerEvent {
+    #[serde(rename_all = "camelCase")]
+    FreshMessageCountChanged { account_id: u32, count: usize },
+    /// A chat list subscribed with `chatlist_subscribe` changed.
+    #[serde(rename_all = "camelCase")]
+    ChatListChanged {
+        subscription_id: u32,
+        account_id: u32,
+        changes: Vec<ChatListChange>,
+    },
+    /// Progress of the `configure` call that is running for the account.
+    ///
+    /// There is at most one `configure` per account at a time,
+    /// so the account id tells which call the progress belongs to.
+    #[serde(rename_all = "camelCase")]
+    ConfigureProgress {
+        account_id: u32,
+        /// 1-999 while configuring, 1000 when done, 0 on failure
+        progress: usize,
+        comment: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ConnectivityChanged {
+        account_id: u32,
+        connectivity: Connectivity,
+    },
+    /// An account was added with `add_account`.
+    #[serde(rename_all = "camelCase")]
+    AccountAdded { account_id: u32 },
+    /// An account was removed with `remove_account`.
+    #[serde(rename_all = "camelCase")]
+    AccountRemoved { account_id: u32 },
+    /// An account was configured successfully with `configure`.
+    #[serde(rename_all = "camelCase")]
+    AccountConfigured { account_id: u32 },
+    /// An account was selected with `select_account`.
+    #[serde(rename_all = "camelCase")]
+    AccountSelected { account_id: u32 },
+}
+
+/// Sends events to all subscribers or to the subscribers of one connection.
+#[derive(Debug)]
+pub(crate) struct Broadcast<T> {
+    subscribers: Arc<Mutex<Vec<Subscriber<T>>>>,
+}
+
+#[derive(Debug)]
+struct Subscriber<T> {
+    connection_id: u32,
+    sender: Sender<T>,
+}
+
+pub(crate) type ServerEvents = Broadcast<ServerEvent>;
+
+impl<T: Clone> Broadcast<T> {
+    pub fn subscribe(&self, connection_id: u32) -> Receiver<T> {
+        let (sender, receiver) = unbounded();
+        self.subscribers.lock().unwrap().push(Subscriber {
+            connection_id,
+            sender,
+        });
+        receiver
+    }
+
+    /// Sends the event to all subscribers, dropping the ones that went away.
+    pub fn emit(&self, event: T) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.sender.try_send(event.clone()).is_ok());
+    }
+
+    /// Sends the event only to the subscribers of the connection, dropping the ones that went away.
+    pub fn emit_to(&self, connection_id: u32, event: T) {
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            subscriber.connection_id != connection_id
+                || subscriber.sender.try_send(event.clone()).is_ok()
+        });
+    }
+}
+
+// not derived, as that would require `T: Clone + Default`
+impl<T> Clone for Broadcast<T> {
+    fn clone(&self) -> Self {
+        Broadcast {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<T> Default for Broadcast<T> {
+    fn default() -> Self {
+        Broadcast {
+            subscribers: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, bail, Context as _, Result};
+use async_std::fs;
+use async_std::path::{Path, PathBuf};
+use futures::StreamExt;
+use std::collections::HashMap;
+
+const STICKER_EXTENSIONS: &[&str] = &["png", "webp", "jpg", "jpeg", "gif"];
+
+/// The stickers of an account are kept next to its blobdir,
+/// in one directory per sticker pack.
+fn stickers_dir(ctx: &deltachat::context::Context) -> Result<PathBuf> {
+    let account_dir = ctx
+        .get_blobdir()
+        .parent()
+        .ok_or_else(|| anyhow!("blobdir has no parent directory"))?;
+    Ok(PathBuf::from(account_dir.join("stickers")))
+}
+
+fn is_sticker_image(path: &Path) -> bool {
+    match path.extension() {
+        Some(extension) => {
+            let extension = extension.to_string_lossy().to_lowercase();
+            STICKER_EXTENSIONS.contains(&extension.as_str())
+        }
+        None => false,
+    }
+}
+
+/// Returns the paths of the sticker images per pack name.
+pub(crate) async fn get_stickers(
+    ctx: &deltachat::context::Context,
+) -> Result<HashMap<String, Vec<String>>> {
+    let dir = stickers_dir(ctx)?;
+    let mut packs = HashMap::new();
+    if !dir.is_dir().await {
+        return Ok(packs);
+    }
+
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        if !entry.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut images = Vec::new();
+        let mut files = fs::read_dir(entry.path()).await?;
+        while let Some(file) = files.next().await {
+            let path = file?.path();
+            if is_sticker_image(&path) {
+                images.push(path.to_string_lossy().into_owned());
+            }
+        }
+        images.sort();
+        packs.insert(entry.file_name().to_string_lossy().into_owned(), images);
+    }
+    Ok(packs)
+}
+
+/// Copies the image into the sticker pack, creating the pack if needed.
+///
+/// Returns the path of the new sticker, existing stickers are not overwritten.
+pub(crate) async fn import_sticker(
+    ctx: &deltachat::context::Context,
+    pack: &str,
+    path: &str,
+) -> Result<String> {
+    if pack.is_empty() || pack == "." || pack == ".." || pack.contains(|c| c == '/' || c == '\\') {
+        bail!("invalid sticker pack name {:?}", pack);
+    }
+    let source = PathBuf::from(path);
+    if !is_sticker_image(&source) {
+        bail!(
+            "{:?} is no sticker image, supported are: {}",
+            path,
+            STICKER_EXTENSIONS.join(", ")
+        );
+    }
+    let stem = source
+        .file_stem()
+        .ok_or_else(|| anyhow!("{:?} has no file name", path))?
+        .to_string_lossy()
+        .into_owned();
+    let extension = source
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let pack_dir = stickers_dir(ctx)?.join(pack);
+    fs::create_dir_all(&pack_dir).await?;
+    let mut target = pack_dir.join(format!("{}.{}", stem, extension));
+    let mut i = 1;
+    while target.exists().await {
+        target = pack_dir.join(format!("{}-{}.{}", stem, i, extension));
+        i += 1;
+    }
+    fs::copy(&source, &target)
+        .await
+        .with_context(|| format!("failed to copy {:?} into sticker pack", path))?;
+    Ok(target.to_string_lossy().into_owned())
+}
@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
 use deltachat::chat::get_chat_contacts;
-use deltachat::chat::{Chat, ChatId};
+use deltachat::chat::get_chat_msgs;
+use deltachat::chat::{Chat, ChatId, ChatItem};
+use deltachat::constants::Chattype;
 use deltachat::contact::{Contact, ContactId};
 use deltachat::context::Context;
 use num_traits::cast::ToPrimitive;
@@ -9,6 +11,8 @@ use typescript_type_def::TypeDef;
 
 use super::color_int_to_hex_string;
 use super::contact::ContactObject;
+use super::message::MessageListItem;
+use crate::api::error::Error;
 
 #[derive(Serialize, TypeDef)]
 pub struct FullChat {
@@ -37,7 +41,9 @@ pub struct FullChat {
 impl FullChat {
     pub async fn from_dc_chat_id(context: &Context, chat_id: u32) -> Result<Self> {
         let rust_chat_id = ChatId::new(chat_id);
-        let chat = Chat::load_from_db(context, rust_chat_id).await?;
+        let chat = Chat::load_from_db(context, rust_chat_id)
+            .await
+            .map_err(|err| Error::or_not_found(err, Error::ChatNotFound { chat_id }))?;
 
         let contact_ids = get_chat_contacts(context, rust_chat_id).await?;
 
@@ -89,3 +95,84 @@ impl FullChat {
         })
     }
 }
+
+/// A chat that was started by someone who is not yet a known contact,
+/// to be accepted with `accept_chat`, declined or blocked.
+#[derive(Serialize, TypeDef)]
+pub struct ContactRequest {
+    chat_id: u32,
+    name: String,
+    /// The contact that sent the request, not set for group chats.
+    contact_id: Option<u32>,
+    is_group: bool,
+    fresh_message_counter: usize,
+    /// The first message of the requester, to decide on the request.
+    /// Load its sender with `contacts_get_contacts_by_ids` if needed.
+    first_message: Option<MessageListItem>,
+}
+
+impl ContactRequest {
+    /// Returns `None` if the chat is no contact request.
+    pub async fn from_dc_chat_id(context: &Context, chat_id: ChatId) -> Result<Option<Self>> {
+        let chat = Chat::load_from_db(context, chat_id).await?;
+        if !chat.is_contact_request() {
+            return Ok(None);
+        }
+
+        let is_group = chat.get_type() != Chattype::Single;
+        let contact_id = match is_group {
+            true => None,
+            false => get_chat_contacts(context, chat_id)
+                .await?
+                .first()
+                .map(|id| id.to_u32()),
+        };
+
+        let first_message_id = get_chat_msgs(context, chat_id, 0, None)
+            .await?
+            .iter()
+            .find_map(|chat_item| match chat_item {
+                ChatItem::Message { msg_id } => Some(msg_id.to_u32()),
+                _ => None,
+            });
+        let first_message = match first_message_id {
+            Some(message_id) => Some(MessageListItem::from_message_id(context, message_id).await?),
+            None => None,
+        };
+
+        Ok(Some(ContactRequest {
+            chat_id: chat_id.to_u32(),
+            name: chat.get_name().to_owned(),
+            contact_id,
+            is_group,
+            fresh_message_counter: chat_id.get_fresh_msg_cnt(context).await?,
+            first_message,
+        }))
+    }
+}
+
+/// A contact request or the error that happened while loading it,
+/// so one broken chat doesn't hide the other requests.
+#[derive(Serialize, TypeDef)]
+#[serde(tag = "type")]
+pub enum ContactRequestFetchResult {
+    ContactRequest(ContactRequest),
+    #[serde(rename_all = "camelCase")]
+    Error {
+        id: u32,
+        error: String,
+    },
+}
+
+impl ContactRequestFetchResult {
+    /// Returns `None` if the chat is no contact request.
+    pub async fn from_dc_chat_id(context: &Context, chat_id: ChatId) -> Option<Self> {
+        match ContactRequest::from_dc_chat_id(context, chat_id).await {
+            Ok(request) => request.map(ContactRequestFetchResult::ContactRequest),
+            Err(err) => Some(ContactRequestFetchResult::Error {
+                id: chat_id.to_u32(),
+                error: format!("{:#}", err),
+            }),
+        }
+    }
+}
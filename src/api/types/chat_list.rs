@@ -1,24 +1,32 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use deltachat::constants::*;
 use deltachat::contact::ContactId;
 use deltachat::{
-    chat::{get_chat_contacts, ChatVisibility},
+    chat::{is_contact_in_chat, ChatVisibility},
     chatlist::Chatlist,
 };
 use deltachat::{
     chat::{Chat, ChatId},
     message::MsgId,
 };
+use futures::stream::{self, StreamExt};
 use num_traits::cast::ToPrimitive;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use typescript_type_def::TypeDef;
 
 use super::color_int_to_hex_string;
 
-#[derive(Deserialize, Serialize, TypeDef)]
+/// How many chat list items are loaded at the same time.
+///
+/// The core has no api to load several chats at once, so every item still needs its own
+/// queries, this only bounds how many of them wait for the database connections at once.
+const CHAT_LIST_ITEM_CONCURRENCY: usize = 16;
+
+#[derive(Deserialize, Serialize, TypeDef, Clone, Debug, PartialEq)]
 pub struct ChatListEntry(pub u32, pub u32);
 
-#[derive(Serialize, TypeDef)]
+#[derive(Serialize, TypeDef, Clone, Debug, PartialEq)]
 #[serde(tag = "type")]
 pub enum ChatListItemFetchResult {
     #[serde(rename_all = "camelCase")]
@@ -51,6 +59,64 @@ pub enum ChatListItemFetchResult {
     },
 }
 
+pub(crate) async fn get_chat_list_entries(
+    ctx: &deltachat::context::Context,
+    list_flags: Option<u32>,
+    query_string: Option<&str>,
+    query_contact_id: Option<u32>,
+) -> Result<Vec<ChatListEntry>> {
+    let list = Chatlist::try_load(
+        ctx,
+        list_flags.unwrap_or(0) as usize,
+        query_string,
+        query_contact_id.map(ContactId::new),
+    )
+    .await?;
+    let mut l: Vec<ChatListEntry> = Vec::new();
+    for i in 0..list.len() {
+        l.push(ChatListEntry(
+            list.get_chat_id(i)?.to_u32(),
+            list.get_msg_id(i)?.unwrap_or_default().to_u32(),
+        ));
+    }
+    Ok(l)
+}
+
+/// Like [`_get_chat_list_items_by_id`], but returns errors as [`ChatListItemFetchResult::Error`].
+pub(crate) async fn get_chat_list_item(
+    ctx: &deltachat::context::Context,
+    entry: &ChatListEntry,
+) -> ChatListItemFetchResult {
+    match _get_chat_list_items_by_id(ctx, entry).await {
+        Ok(res) => res,
+        Err(err) => ChatListItemFetchResult::Error {
+            id: entry.0,
+            error: format!("{:?}", err),
+        },
+    }
+}
+
+/// Loads the items of the entries concurrently, see [`get_chat_list_item`].
+///
+/// The database work is not batched, the database of the core is not accessible from here,
+/// each item is loaded with the per chat functions of the core.
+/// Entries of the same chat are only loaded once.
+pub(crate) async fn get_chat_list_items(
+    ctx: &deltachat::context::Context,
+    entries: &[ChatListEntry],
+) -> HashMap<u32, ChatListItemFetchResult> {
+    let mut seen = HashSet::new();
+    let entries: Vec<&ChatListEntry> = entries
+        .iter()
+        .filter(|entry| seen.insert(entry.0))
+        .collect();
+    stream::iter(entries)
+        .map(|entry| async move { (entry.0, get_chat_list_item(ctx, entry).await) })
+        .buffer_unordered(CHAT_LIST_ITEM_CONCURRENCY)
+        .collect()
+        .await
+}
+
 pub(crate) async fn _get_chat_list_items_by_id(
     ctx: &deltachat::context::Context,
     entry: &ChatListEntry,
@@ -78,17 +144,18 @@ pub(crate) async fn _get_chat_list_items_by_id(
         .await?
         .map(|path| path.to_str().unwrap_or("invalid/path").to_owned());
 
+    // the summary already loaded the last message, no need to load it again,
+    // but it falls back to an empty summary without timestamp if loading failed
     let last_updated = match last_msgid {
-        Some(id) => {
-            let last_message = deltachat::message::Message::load_from_db(ctx, id).await?;
-            Some(last_message.get_timestamp() * 1000)
+        Some(msg_id) if summary.timestamp == 0 => {
+            bail!("can't load last message {} of chat {}", msg_id, chat_id)
         }
+        Some(_) => Some(summary.timestamp * 1000),
         None => None,
     };
 
-    let self_in_group = get_chat_contacts(ctx, chat_id)
-        .await?
-        .contains(&ContactId::SELF);
+    // only check for self instead of loading all members of the chat
+    let self_in_group = is_contact_in_chat(ctx, chat_id, ContactId::SELF).await?;
 
     let fresh_message_counter = chat_id.get_fresh_msg_cnt(ctx).await?;
     let color = color_int_to_hex_string(chat.get_color(ctx).await?);
@@ -0,0 +1,357 @@
+use anyhow::{bail, Result};
+use deltachat::config::Config;
+use deltachat::context::Context;
+use serde::{Deserialize, Serialize};
+use strum::EnumProperty;
+use typescript_type_def::TypeDef;
+
+#[derive(Serialize, Deserialize, TypeDef, Clone, Copy, PartialEq)]
+pub enum ShowEmails {
+    Off,
+    AcceptedContacts,
+    All,
+}
+
+impl ShowEmails {
+    fn from_config(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(ShowEmails::Off),
+            1 => Some(ShowEmails::AcceptedContacts),
+            2 => Some(ShowEmails::All),
+            _ => None,
+        }
+    }
+
+    fn to_config(self) -> i32 {
+        match self {
+            ShowEmails::Off => 0,
+            ShowEmails::AcceptedContacts => 1,
+            ShowEmails::All => 2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, TypeDef, Clone, Copy, PartialEq)]
+pub enum MediaQuality {
+    Balanced,
+    Worse,
+}
+
+impl MediaQuality {
+    fn from_config(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(MediaQuality::Balanced),
+            1 => Some(MediaQuality::Worse),
+            _ => None,
+        }
+    }
+
+    fn to_config(self) -> i32 {
+        match self {
+            MediaQuality::Balanced => 0,
+            MediaQuality::Worse => 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, TypeDef, Clone, Copy, PartialEq)]
+pub enum VideochatType {
+    /// The url is sent as is, without a specific videochat client.
+    Unknown,
+    BasicWebrtc,
+    Jitsi,
+}
+
+/// The videochat instance used for videochat invitations, the `webrtc_instance` config.
+#[derive(Serialize, Deserialize, TypeDef, Clone, PartialEq)]
+pub struct WebrtcInstance {
+    pub videochat_type: VideochatType,
+    /// http(s) url of the instance, `$ROOM` is replaced with a random room name.
+    pub url: String,
+}
+
+impl WebrtcInstance {
+    pub fn from_config(value: &str) -> Self {
+        let (videochat_type, url) = if let Some(url) = value.strip_prefix("basicwebrtc:") {
+            (VideochatType::BasicWebrtc, url)
+        } else if let Some(url) = value.strip_prefix("jitsi:") {
+            (VideochatType::Jitsi, url)
+        } else {
+            (VideochatType::Unknown, value)
+        };
+        WebrtcInstance {
+            videochat_type,
+            url: url.to_owned(),
+        }
+    }
+
+    pub fn to_config(&self) -> Result<String> {
+        let url = self.url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) || url.contains(' ') {
+            bail!("{:?} is no valid http(s) url", self.url);
+        }
+        Ok(match self.videochat_type {
+            VideochatType::Unknown => url.to_owned(),
+            VideochatType::BasicWebrtc => format!("basicwebrtc:{}", url),
+            VideochatType::Jitsi => format!("jitsi:{}", url),
+        })
+    }
+}
+
+/// Typed subset of the account configuration.
+///
+/// When reading, every field is set, except `displayname` and `selfstatus` when they are unset.
+/// When writing, only the fields that are set are changed, the others can be null or left out.
+#[derive(Serialize, Deserialize, TypeDef, Default)]
+pub struct AccountSettings {
+    #[serde(default)]
+    pub displayname: Option<String>,
+    #[serde(default)]
+    pub selfstatus: Option<String>,
+    #[serde(default)]
+    pub e2ee_enabled: Option<bool>,
+    #[serde(default)]
+    pub mdns_enabled: Option<bool>,
+    #[serde(default)]
+    pub bcc_self: Option<bool>,
+    #[serde(default)]
+    pub sentbox_watch: Option<bool>,
+    #[serde(default)]
+    pub mvbox_move: Option<bool>,
+    #[serde(default)]
+    pub only_fetch_mvbox: Option<bool>,
+    #[serde(default)]
+    pub fetch_existing_msgs: Option<bool>,
+    #[serde(default)]
+    pub bot: Option<bool>,
+    #[serde(default)]
+    pub show_emails: Option<ShowEmails>,
+    #[serde(default)]
+    pub media_quality: Option<MediaQuality>,
+    /// seconds, 0 means never
+    #[serde(default)]
+    pub delete_device_after: Option<i64>,
+    /// seconds, 0 means never, 1 means at once
+    #[serde(default)]
+    pub delete_server_after: Option<i64>,
+    /// bytes, bigger messages are only downloaded partially, 0 means no limit
+    #[serde(default)]
+    pub download_limit: Option<i64>,
+}
+
+impl AccountSettings {
+    pub async fn from_context(ctx: &Context) -> Result<Self> {
+        Ok(AccountSettings {
+            displayname: ctx.get_config(Config::Displayname).await?,
+            selfstatus: ctx.get_config(Config::Selfstatus).await?,
+            e2ee_enabled: Some(ctx.get_config_bool(Config::E2eeEnabled).await?),
+            mdns_enabled: Some(ctx.get_config_bool(Config::MdnsEnabled).await?),
+            bcc_self: Some(ctx.get_config_bool(Config::BccSelf).await?),
+            sentbox_watch: Some(ctx.get_config_bool(Config::SentboxWatch).await?),
+            mvbox_move: Some(ctx.get_config_bool(Config::MvboxMove).await?),
+            only_fetch_mvbox: Some(ctx.get_config_bool(Config::OnlyFetchMvbox).await?),
+            fetch_existing_msgs: Some(ctx.get_config_bool(Config::FetchExistingMsgs).await?),
+            bot: Some(ctx.get_config_bool(Config::Bot).await?),
+            // values that were stored with `set_config` may be out of range, show the default then
+            show_emails: Some(
+                ShowEmails::from_config(ctx.get_config_int(Config::ShowEmails).await?)
+                    .unwrap_or(ShowEmails::All),
+            ),
+            media_quality: Some(
+                MediaQuality::from_config(ctx.get_config_int(Config::MediaQuality).await?)
+                    .unwrap_or(MediaQuality::Balanced),
+            ),
+            delete_device_after: Some(ctx.get_config_i64(Config::DeleteDeviceAfter).await?),
+            delete_server_after: Some(ctx.get_config_i64(Config::DeleteServerAfter).await?),
+            download_limit: Some(ctx.get_config_i64(Config::DownloadLimit).await?),
+        })
+    }
+
+    /// Writes all fields that are set to the account configuration.
+    pub async fn apply(self, ctx: &Context) -> Result<()> {
+        if let Some(displayname) = self.displayname {
+            ctx.set_config(Config::Displayname, Some(&displayname))
+                .await?;
+        }
+        if let Some(selfstatus) = self.selfstatus {
+            ctx.set_config(Config::Selfstatus, Some(&selfstatus))
+                .await?;
+        }
+        for (key, value) in [
+            (Config::E2eeEnabled, self.e2ee_enabled),
+            (Config::MdnsEnabled, self.mdns_enabled),
+            (Config::BccSelf, self.bcc_self),
+            (Config::SentboxWatch, self.sentbox_watch),
+            (Config::MvboxMove, self.mvbox_move),
+            (Config::OnlyFetchMvbox, self.only_fetch_mvbox),
+            (Config::FetchExistingMsgs, self.fetch_existing_msgs),
+            (Config::Bot, self.bot),
+        ] {
+            if let Some(value) = value {
+                ctx.set_config(key, Some(if value { "1" } else { "0" }))
+                    .await?;
+            }
+        }
+        if let Some(show_emails) = self.show_emails {
+            ctx.set_config(
+                Config::ShowEmails,
+                Some(&show_emails.to_config().to_string()),
+            )
+            .await?;
+        }
+        if let Some(media_quality) = self.media_quality {
+            ctx.set_config(
+                Config::MediaQuality,
+                Some(&media_quality.to_config().to_string()),
+            )
+            .await?;
+        }
+        if let Some(delete_device_after) = self.delete_device_after {
+            ctx.set_config(
+                Config::DeleteDeviceAfter,
+                Some(&delete_device_after.to_string()),
+            )
+            .await?;
+        }
+        if let Some(delete_server_after) = self.delete_server_after {
+            ctx.set_config(
+                Config::DeleteServerAfter,
+                Some(&delete_server_after.to_string()),
+            )
+            .await?;
+        }
+        if let Some(download_limit) = self.download_limit {
+            ctx.set_config(Config::DownloadLimit, Some(&download_limit.to_string()))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, TypeDef)]
+#[serde(tag = "type")]
+pub enum ConfigValueType {
+    String,
+    Boolean,
+    Integer,
+    Enum { variants: Vec<ConfigEnumVariant> },
+}
+
+/// A possible value of an enum config key.
+#[derive(Serialize, TypeDef)]
+pub struct ConfigEnumVariant {
+    /// The value to pass to `set_config`.
+    value: String,
+    name: String,
+}
+
+impl ConfigValueType {
+    fn enumeration(variants: &[(&str, &str)]) -> Self {
+        ConfigValueType::Enum {
+            variants: variants
+                .iter()
+                .map(|(value, name)| ConfigEnumVariant {
+                    value: (*value).to_owned(),
+                    name: (*name).to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, TypeDef)]
+pub struct ConfigKeyInfo {
+    key: String,
+    value_type: ConfigValueType,
+    default: Option<String>,
+    description: String,
+}
+
+impl ConfigKeyInfo {
+    fn new(key: Config, value_type: ConfigValueType, description: &str) -> Self {
+        ConfigKeyInfo {
+            key: key.to_string(),
+            value_type,
+            default: key.get_str("default").map(|s| s.to_owned()),
+            description: description.to_owned(),
+        }
+    }
+}
+
+/// Describes the config keys that are meant to be changed by the user.
+pub fn get_config_keys() -> Vec<ConfigKeyInfo> {
+    use ConfigValueType::*;
+    vec![
+        ConfigKeyInfo::new(Config::Addr, String, "Email address"),
+        ConfigKeyInfo::new(Config::MailServer, String, "IMAP server hostname"),
+        ConfigKeyInfo::new(Config::MailUser, String, "IMAP server username"),
+        ConfigKeyInfo::new(Config::MailPw, String, "IMAP server password"),
+        ConfigKeyInfo::new(Config::MailPort, Integer, "IMAP server port"),
+        ConfigKeyInfo::new(Config::SendServer, String, "SMTP server hostname"),
+        ConfigKeyInfo::new(Config::SendUser, String, "SMTP server username"),
+        ConfigKeyInfo::new(Config::SendPw, String, "SMTP server password"),
+        ConfigKeyInfo::new(Config::SendPort, Integer, "SMTP server port"),
+        ConfigKeyInfo::new(Config::ServerFlags, Integer, "IMAP/SMTP server flags"),
+        ConfigKeyInfo::new(Config::Socks5Enabled, Boolean, "Use a SOCKS5 proxy"),
+        ConfigKeyInfo::new(Config::Socks5Host, String, "SOCKS5 proxy hostname"),
+        ConfigKeyInfo::new(Config::Socks5Port, Integer, "SOCKS5 proxy port"),
+        ConfigKeyInfo::new(Config::Socks5User, String, "SOCKS5 proxy username"),
+        ConfigKeyInfo::new(Config::Socks5Password, String, "SOCKS5 proxy password"),
+        ConfigKeyInfo::new(Config::Displayname, String, "Own name shown to others"),
+        ConfigKeyInfo::new(Config::Selfstatus, String, "Own status, used as signature"),
+        ConfigKeyInfo::new(Config::Selfavatar, String, "Path to the own avatar"),
+        ConfigKeyInfo::new(Config::E2eeEnabled, Boolean, "Prefer end-to-end encryption"),
+        ConfigKeyInfo::new(
+            Config::MdnsEnabled,
+            Boolean,
+            "Send and request read receipts",
+        ),
+        ConfigKeyInfo::new(
+            Config::BccSelf,
+            Boolean,
+            "Send a copy of outgoing messages to self",
+        ),
+        ConfigKeyInfo::new(Config::SentboxWatch, Boolean, "Watch the Sent folder"),
+        ConfigKeyInfo::new(
+            Config::MvboxMove,
+            Boolean,
+            "Move chat messages to the DeltaChat folder",
+        ),
+        ConfigKeyInfo::new(
+            Config::OnlyFetchMvbox,
+            Boolean,
+            "Only fetch messages from the DeltaChat folder",
+        ),
+        ConfigKeyInfo::new(
+            Config::FetchExistingMsgs,
+            Boolean,
+            "Fetch existing messages on configure",
+        ),
+        ConfigKeyInfo::new(Config::Bot, Boolean, "Account is a bot"),
+        ConfigKeyInfo::new(
+            Config::ShowEmails,
+            ConfigValueType::enumeration(&[("0", "Off"), ("1", "AcceptedContacts"), ("2", "All")]),
+            "Which classic emails to show",
+        ),
+        ConfigKeyInfo::new(
+            Config::MediaQuality,
+            ConfigValueType::enumeration(&[("0", "Balanced"), ("1", "Worse")]),
+            "Quality of outgoing images and videos",
+        ),
+        ConfigKeyInfo::new(
+            Config::DeleteDeviceAfter,
+            Integer,
+            "Delete messages from the device after this many seconds, 0=never",
+        ),
+        ConfigKeyInfo::new(
+            Config::DeleteServerAfter,
+            Integer,
+            "Delete messages from the server after this many seconds, 0=never, 1=at once",
+        ),
+        ConfigKeyInfo::new(
+            Config::DownloadLimit,
+            Integer,
+            "Only download messages up to this many bytes completely, 0=no limit",
+        ),
+    ]
+}
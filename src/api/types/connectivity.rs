@@ -0,0 +1,25 @@
+use serde::Serialize;
+use typescript_type_def::TypeDef;
+
+/// Connectivity of an account, ordered from worst to best.
+#[derive(Serialize, TypeDef, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Connectivity {
+    NotConnected,
+    Connecting,
+    /// Connected, but currently fetching or sending messages.
+    Working,
+    Connected,
+}
+
+impl Connectivity {
+    pub async fn from_context(ctx: &deltachat::context::Context) -> Self {
+        // the core documents its connectivity values as ranges,
+        // so new intermediate states still map to the right one
+        match ctx.get_connectivity().await as u32 {
+            value if value >= 4000 => Connectivity::Connected,
+            value if value >= 3000 => Connectivity::Working,
+            value if value >= 2000 => Connectivity::Connecting,
+            _ => Connectivity::NotConnected,
+        }
+    }
+}
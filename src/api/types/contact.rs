@@ -1,10 +1,11 @@
 use anyhow::Result;
-use deltachat::contact::VerifiedStatus;
+use deltachat::contact::{Contact, ContactId, VerifiedStatus};
 use deltachat::context::Context;
 use serde::Serialize;
 use typescript_type_def::TypeDef;
 
 use super::color_int_to_hex_string;
+use crate::api::error::Error;
 
 #[derive(Serialize, TypeDef)]
 #[serde(rename = "Contact")]
@@ -48,3 +49,37 @@ impl ContactObject {
         })
     }
 }
+
+/// Result of loading one contact of a batch, see `MessageFetchResult`.
+#[derive(Serialize, TypeDef)]
+#[serde(tag = "type")]
+pub enum ContactFetchResult {
+    Contact(ContactObject),
+    #[serde(rename_all = "camelCase")]
+    Error {
+        id: u32,
+        error: String,
+    },
+}
+
+impl ContactFetchResult {
+    pub async fn from_contact_id(context: &Context, contact_id: u32) -> Self {
+        let contact = match Contact::get_by_id(context, ContactId::new(contact_id)).await {
+            Ok(contact) => contact,
+            Err(err) => {
+                return ContactFetchResult::Error {
+                    id: contact_id,
+                    error: Error::or_not_found(err, Error::ContactNotFound { contact_id })
+                        .to_string(),
+                }
+            }
+        };
+        match ContactObject::from_dc_contact(context, contact).await {
+            Ok(contact) => ContactFetchResult::Contact(contact),
+            Err(err) => ContactFetchResult::Error {
+                id: contact_id,
+                error: format!("{:#}", err),
+            },
+        }
+    }
+}
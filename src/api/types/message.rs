@@ -3,11 +3,14 @@ use deltachat::contact::Contact;
 use deltachat::context::Context;
 use deltachat::message::Message;
 use deltachat::message::MsgId;
+use deltachat::message::Viewtype;
 use num_traits::cast::ToPrimitive;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use typescript_type_def::TypeDef;
 
-use super::contact::ContactObject;
+use super::contact::{ContactFetchResult, ContactObject};
+use crate::api::error::Error;
 
 #[derive(Serialize, TypeDef)]
 #[serde(rename = "Message")]
@@ -51,12 +54,37 @@ pub struct MessageObject {
     file_mime: Option<String>,
     file_bytes: u64,
     file_name: Option<String>,
+
+    download_state: DownloadState,
+}
+
+/// Messages bigger than the `download_limit` are only downloaded partially,
+/// see `download_full_message`.
+#[derive(Serialize, TypeDef)]
+pub enum DownloadState {
+    Done,
+    Available,
+    Failure,
+    InProgress,
+}
+
+impl From<deltachat::download::DownloadState> for DownloadState {
+    fn from(state: deltachat::download::DownloadState) -> Self {
+        match state {
+            deltachat::download::DownloadState::Done => DownloadState::Done,
+            deltachat::download::DownloadState::Available => DownloadState::Available,
+            deltachat::download::DownloadState::Failure => DownloadState::Failure,
+            deltachat::download::DownloadState::InProgress => DownloadState::InProgress,
+        }
+    }
 }
 
 impl MessageObject {
     pub async fn from_message_id(context: &Context, message_id: u32) -> Result<Self> {
         let msg_id = MsgId::new(message_id);
-        let message = Message::load_from_db(context, msg_id).await?;
+        let message = Message::load_from_db(context, msg_id)
+            .await
+            .map_err(|err| Error::or_not_found(err, Error::MessageNotFound { message_id }))?;
 
         let quoted_message_id = message
             .quoted_message(context)
@@ -122,6 +150,190 @@ impl MessageObject {
             file_mime: message.get_filemime(),
             file_bytes,
             file_name: message.get_filename(),
+
+            download_state: message.download_state().into(),
         })
     }
 }
+
+/// Result of loading one message of a batch, so one message that can't be loaded
+/// (for example because it was deleted meanwhile) doesn't fail the whole batch.
+#[derive(Serialize, TypeDef)]
+#[serde(tag = "type")]
+pub enum MessageFetchResult {
+    Message(MessageObject),
+    #[serde(rename_all = "camelCase")]
+    Error {
+        id: u32,
+        error: String,
+    },
+}
+
+impl MessageFetchResult {
+    pub async fn from_message_id(context: &Context, message_id: u32) -> Self {
+        match MessageObject::from_message_id(context, message_id).await {
+            Ok(message) => MessageFetchResult::Message(message),
+            Err(err) => MessageFetchResult::Error {
+                id: message_id,
+                error: format!("{:#}", err),
+            },
+        }
+    }
+}
+
+/// A slimmer version of [`MessageObject`] for rendering message lists.
+///
+/// The sender is only referenced by `from_id`, the contacts are sent separately,
+/// and everything that needs additional database or file system access is left out
+/// (quoted message id, setup code, file size).
+#[derive(Serialize, TypeDef)]
+pub struct MessageListItem {
+    id: u32,
+    chat_id: u32,
+    from_id: u32,
+    quoted_text: Option<String>,
+    text: Option<String>,
+    has_location: bool,
+    has_html: bool,
+    view_type: u32,
+    state: u32,
+
+    timestamp: i64,
+    sort_timestamp: i64,
+    has_deviating_timestamp: bool,
+
+    show_padlock: bool,
+    is_setupmessage: bool,
+    is_info: bool,
+    is_forwarded: bool,
+
+    duration: i32,
+    dimensions_height: i32,
+    dimensions_width: i32,
+
+    override_sender_name: Option<String>,
+
+    file: Option<String>,
+    file_mime: Option<String>,
+    file_name: Option<String>,
+
+    download_state: DownloadState,
+}
+
+impl MessageListItem {
+    pub async fn from_message_id(context: &Context, message_id: u32) -> Result<Self> {
+        let message = Message::load_from_db(context, MsgId::new(message_id))
+            .await
+            .map_err(|err| Error::or_not_found(err, Error::MessageNotFound { message_id }))?;
+
+        Ok(MessageListItem {
+            id: message_id,
+            chat_id: message.get_chat_id().to_u32(),
+            from_id: message.get_from_id().to_u32(),
+            quoted_text: message.quoted_text(),
+            text: message.get_text(),
+            has_location: message.has_location(),
+            has_html: message.has_html(),
+            view_type: message
+                .get_viewtype()
+                .to_u32()
+                .ok_or_else(|| anyhow!("viewtype conversion to number failed"))?,
+            state: message
+                .get_state()
+                .to_u32()
+                .ok_or_else(|| anyhow!("state conversion to number failed"))?,
+
+            timestamp: message.get_timestamp(),
+            sort_timestamp: message.get_sort_timestamp(),
+            has_deviating_timestamp: message.has_deviating_timestamp(),
+
+            show_padlock: message.get_showpadlock(),
+            is_setupmessage: message.is_setupmessage(),
+            is_info: message.is_info(),
+            is_forwarded: message.is_forwarded(),
+
+            duration: message.get_duration(),
+            dimensions_height: message.get_height(),
+            dimensions_width: message.get_width(),
+
+            override_sender_name: message.get_override_sender_name(),
+
+            file: match message.get_file(context) {
+                Some(path_buf) => path_buf.to_str().map(|s| s.to_owned()),
+                None => None,
+            }, //BLOBS
+            file_mime: message.get_filemime(),
+            file_name: message.get_filename(),
+
+            download_state: message.download_state().into(),
+        })
+    }
+}
+
+/// Like [`MessageFetchResult`], for [`MessageListItem`]s.
+#[derive(Serialize, TypeDef)]
+#[serde(tag = "type")]
+pub enum MessageListItemFetchResult {
+    MessageListItem(MessageListItem),
+    #[serde(rename_all = "camelCase")]
+    Error {
+        id: u32,
+        error: String,
+    },
+}
+
+impl MessageListItemFetchResult {
+    pub async fn from_message_id(context: &Context, message_id: u32) -> Self {
+        match MessageListItem::from_message_id(context, message_id).await {
+            Ok(message) => MessageListItemFetchResult::MessageListItem(message),
+            Err(err) => MessageListItemFetchResult::Error {
+                id: message_id,
+                error: format!("{:#}", err),
+            },
+        }
+    }
+
+    /// The sender of the message, if it could be loaded.
+    pub fn from_id(&self) -> Option<u32> {
+        match self {
+            MessageListItemFetchResult::MessageListItem(message) => Some(message.from_id),
+            MessageListItemFetchResult::Error { .. } => None,
+        }
+    }
+}
+
+/// Messages of a message list together with their senders.
+#[derive(Serialize, TypeDef)]
+pub struct MessageListItems {
+    pub messages: HashMap<u32, MessageListItemFetchResult>,
+    /// The senders of the messages, by contact id.
+    pub contacts: HashMap<u32, ContactFetchResult>,
+}
+
+/// Viewtypes of messages with media, used to filter the media of a chat.
+#[derive(Serialize, Deserialize, TypeDef, Clone, Copy)]
+pub enum MessageViewtype {
+    Image,
+    Gif,
+    Sticker,
+    Audio,
+    Voice,
+    Video,
+    File,
+    Webxdc,
+}
+
+impl From<MessageViewtype> for Viewtype {
+    fn from(viewtype: MessageViewtype) -> Self {
+        match viewtype {
+            MessageViewtype::Image => Viewtype::Image,
+            MessageViewtype::Gif => Viewtype::Gif,
+            MessageViewtype::Sticker => Viewtype::Sticker,
+            MessageViewtype::Audio => Viewtype::Audio,
+            MessageViewtype::Voice => Viewtype::Voice,
+            MessageViewtype::Video => Viewtype::Video,
+            MessageViewtype::File => Viewtype::File,
+            MessageViewtype::Webxdc => Viewtype::Webxdc,
+        }
+    }
+}
@@ -1,9 +1,12 @@
 pub mod account;
 pub mod chat;
 pub mod chat_list;
+pub mod config;
+pub mod connectivity;
 pub mod contact;
 pub mod message;
 pub mod provider_info;
+pub mod webxdc;
 
 pub fn color_int_to_hex_string(color: u32) -> String {
     format!("{:#08x}", color).replace("0x", "#")
@@ -1,21 +1,125 @@
-use deltachat::provider::Provider;
+use deltachat::provider::{
+    ConfigDefault, Oauth2Authorizer, Protocol, Provider, Server, Socket, UsernamePattern,
+};
 use num_traits::cast::ToPrimitive;
 use serde::Serialize;
 use typescript_type_def::TypeDef;
 
 #[derive(Serialize, TypeDef)]
 pub struct ProviderInfo {
+    pub id: String,
     pub before_login_hint: String,
+    pub after_login_hint: String,
     pub overview_page: String,
     pub status: u32, // in reality this is an enum, but for simlicity and because it gets converted into a number anyway, we use an u32 here.
+    /// Set if the provider requires logging in with OAuth2, see `get_oauth2_url`.
+    pub oauth2_authorizer: Option<ProviderOauth2Authorizer>,
+    pub strict_tls: bool,
+    /// Known IMAP and SMTP servers of the provider, in the order they should be tried.
+    pub servers: Vec<ProviderServer>,
+    /// Config values that are set when configuring an account of this provider.
+    pub config_defaults: Vec<ProviderConfigDefault>,
+}
+
+#[derive(Serialize, TypeDef)]
+pub enum ProviderOauth2Authorizer {
+    Yandex,
+    Gmail,
+}
+
+#[derive(Serialize, TypeDef)]
+pub struct ProviderServer {
+    pub protocol: ServerProtocol,
+    pub socket: ServerSocket,
+    pub hostname: String,
+    pub port: u16,
+    pub username_pattern: ServerUsernamePattern,
+}
+
+#[derive(Serialize, TypeDef)]
+pub enum ServerProtocol {
+    Imap,
+    Smtp,
+}
+
+#[derive(Serialize, TypeDef)]
+pub enum ServerSocket {
+    Automatic,
+    Ssl,
+    Starttls,
+    Plain,
+}
+
+#[derive(Serialize, TypeDef)]
+pub enum ServerUsernamePattern {
+    /// the full email address
+    Email,
+    /// the part of the email address before the `@`
+    EmailLocalPart,
+}
+
+#[derive(Serialize, TypeDef)]
+pub struct ProviderConfigDefault {
+    pub key: String,
+    pub value: String,
 }
 
 impl ProviderInfo {
     pub fn from_dc_type(provider: Option<&Provider>) -> Option<Self> {
         provider.map(|p| ProviderInfo {
+            id: p.id.to_owned(),
             before_login_hint: p.before_login_hint.to_owned(),
+            after_login_hint: p.after_login_hint.to_owned(),
             overview_page: p.overview_page.to_owned(),
             status: p.status.to_u32().unwrap(),
+            oauth2_authorizer: p.oauth2_authorizer.as_ref().map(|a| match a {
+                Oauth2Authorizer::Yandex => ProviderOauth2Authorizer::Yandex,
+                Oauth2Authorizer::Gmail => ProviderOauth2Authorizer::Gmail,
+            }),
+            strict_tls: p.strict_tls,
+            servers: p.server.iter().map(ProviderServer::from_dc_type).collect(),
+            config_defaults: p
+                .config_defaults
+                .as_ref()
+                .map(|defaults| {
+                    defaults
+                        .iter()
+                        .map(ProviderConfigDefault::from_dc_type)
+                        .collect()
+                })
+                .unwrap_or_default(),
         })
     }
 }
+
+impl ProviderServer {
+    fn from_dc_type(server: &Server) -> Self {
+        ProviderServer {
+            protocol: match server.protocol {
+                Protocol::Imap => ServerProtocol::Imap,
+                Protocol::Smtp => ServerProtocol::Smtp,
+            },
+            socket: match server.socket {
+                Socket::Automatic => ServerSocket::Automatic,
+                Socket::Ssl => ServerSocket::Ssl,
+                Socket::Starttls => ServerSocket::Starttls,
+                Socket::Plain => ServerSocket::Plain,
+            },
+            hostname: server.hostname.to_owned(),
+            port: server.port,
+            username_pattern: match server.username_pattern {
+                UsernamePattern::Email => ServerUsernamePattern::Email,
+                UsernamePattern::Emaillocalpart => ServerUsernamePattern::EmailLocalPart,
+            },
+        }
+    }
+}
+
+impl ProviderConfigDefault {
+    fn from_dc_type(config_default: &ConfigDefault) -> Self {
+        ProviderConfigDefault {
+            key: config_default.key.to_string(),
+            value: config_default.value.to_owned(),
+        }
+    }
+}
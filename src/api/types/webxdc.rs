@@ -0,0 +1,35 @@
+use anyhow::Result;
+use deltachat::context::Context;
+use deltachat::message::{Message, MsgId, Viewtype};
+use serde::Serialize;
+use typescript_type_def::TypeDef;
+
+use crate::api::error::Error;
+
+#[derive(Serialize, TypeDef)]
+pub struct WebxdcMessageInfo {
+    /// The name of the app, taken from the manifest or the filename of the .xdc file.
+    name: String,
+    /// Path of the icon inside the .xdc file, load it with `webxdc_get_blob`.
+    icon: String,
+    /// The summary that was last set by the app with a status update.
+    summary: String,
+}
+
+impl WebxdcMessageInfo {
+    pub async fn from_message_id(context: &Context, message_id: u32) -> Result<Self> {
+        let message = Message::load_from_db(context, MsgId::new(message_id))
+            .await
+            .map_err(|err| Error::or_not_found(err, Error::MessageNotFound { message_id }))?;
+        if message.get_viewtype() != Viewtype::Webxdc {
+            return Err(Error::NotWebxdcMessage { message_id }.into());
+        }
+        let info = message.get_webxdc_info(context).await?;
+
+        Ok(WebxdcMessageInfo {
+            name: info.name,
+            icon: info.icon,
+            summary: info.summary,
+        })
+    }
+}
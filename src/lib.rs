@@ -3,11 +3,16 @@ pub use api::events;
 
 #[cfg(test)]
 mod tests {
-    use super::api::{Accounts, CommandApi};
-    use async_channel::unbounded;
+    use super::api::error::ErrorData;
+    use super::api::server_events::ServerEvent;
+    use super::api::{batch, Accounts, CommandApi};
+    use async_channel::{unbounded, Receiver};
     use async_std::task;
     use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::time::{Duration, Instant};
     use tempfile::TempDir;
+    use typescript_type_def::{write_definition_file, DefinitionFileOptions};
     use yerpc::{MessageHandle, RpcHandle};
 
     #[async_std::test]
@@ -55,4 +60,173 @@ mod tests {
 
         Ok(())
     }
+
+    #[async_std::test]
+    async fn batch_requests() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new().unwrap().path().into();
+        let accounts = Accounts::new(tmp_dir).await?;
+        let cmd_api = CommandApi::new(accounts);
+
+        let (request_handle, _rx) = RpcHandle::new();
+        let handle = MessageHandle::new(request_handle, cmd_api.clone());
+
+        let request = r#"[
+            {"jsonrpc":"2.0","method":"add_account","params":[],"id":1},
+            {"jsonrpc":"2.0","method":"check_email_validity","params":["email@example.com"],"id":2},
+            {"jsonrpc":"2.0","method":"no_such_method","params":[],"id":3},
+            {"jsonrpc":"2.0","method":"add_account","params":[]},
+            {"jsonrpc":"2.0","id":4}
+        ]"#;
+        let response = batch::handle_message(&handle, &cmd_api, request)
+            .await
+            .expect("no batch response");
+        let response: Value = serde_json::from_str(&response)?;
+        let responses = response.as_array().expect("batch response is no array");
+        // no response for the notification
+        assert_eq!(responses.len(), 4);
+        let by_id = |id: u32| {
+            responses
+                .iter()
+                .find(|response| response["id"] == id)
+                .expect("response missing")
+        };
+        assert!(by_id(1)["result"].is_u64());
+        assert_eq!(by_id(2)["result"], true);
+        assert!(by_id(3)["error"]["code"].is_i64());
+        assert_eq!(by_id(4)["error"]["code"], -32600);
+
+        // both add_account calls were executed
+        let account_ids = call_batch(&handle, &cmd_api, "get_all_account_ids").await?;
+        assert_eq!(account_ids.as_array().map(|ids| ids.len()), Some(2));
+
+        assert_eq!(
+            batch::handle_message(&handle, &cmd_api, "[]").await,
+            Some(r#"{"error":{"code":-32600,"message":"Invalid Request"},"id":null,"jsonrpc":"2.0"}"#.to_owned())
+        );
+
+        Ok(())
+    }
+
+    /// Sends a request without parameters as batch and returns its result.
+    async fn call_batch(
+        handle: &MessageHandle<CommandApi>,
+        cmd_api: &CommandApi,
+        method: &str,
+    ) -> anyhow::Result<Value> {
+        let request = json!([{"jsonrpc": "2.0", "method": method, "params": [], "id": 1}]);
+        let response = batch::handle_message(handle, cmd_api, &request.to_string())
+            .await
+            .expect("no batch response");
+        let mut response: Value = serde_json::from_str(&response)?;
+        Ok(response[0]["result"].take())
+    }
+
+    /// Sends a request and returns the result of the response.
+    async fn call(
+        handle: &MessageHandle<CommandApi>,
+        receiver: &mut Receiver<String>,
+        id: u32,
+        method: &str,
+        params: Value,
+    ) -> anyhow::Result<Value> {
+        let request = json!({"jsonrpc": "2.0", "method": method, "params": params, "id": id});
+        handle.handle_message(&request.to_string()).await;
+        let response = receiver.next().await.expect("no response");
+        let mut response: Value = serde_json::from_str(&response)?;
+        Ok(response["result"].take())
+    }
+
+    #[async_std::test]
+    async fn chat_list_items_benchmark() -> anyhow::Result<()> {
+        const CHAT_COUNT: u32 = 200;
+        // generous for slow CI machines, it only catches loading the items getting a lot slower
+        const MAX_DURATION: Duration = Duration::from_secs(10);
+
+        let tmp_dir = TempDir::new().unwrap().path().into();
+        let accounts = Accounts::new(tmp_dir).await?;
+        let cmd_api = CommandApi::new(accounts);
+
+        let (sender, mut receiver) = unbounded::<String>();
+        let (request_handle, mut rx) = RpcHandle::new();
+        let handle = MessageHandle::new(request_handle, cmd_api);
+        task::spawn(async move {
+            while let Some(message) = rx.next().await {
+                let message = serde_json::to_string(&message)?;
+                sender.send(message).await?;
+            }
+            let res: Result<(), anyhow::Error> = Ok(());
+            res
+        });
+
+        let mut id = 0;
+        let mut next_id = || {
+            id += 1;
+            id
+        };
+        let account_id = call(&handle, &mut receiver, next_id(), "add_account", json!([])).await?;
+        for i in 0..CHAT_COUNT {
+            let addr = format!("contact{}@example.org", i);
+            let contact_id = call(
+                &handle,
+                &mut receiver,
+                next_id(),
+                "contacts_create_contact",
+                json!([account_id, addr, null]),
+            )
+            .await?;
+            call(
+                &handle,
+                &mut receiver,
+                next_id(),
+                "contacts_create_chat_by_contact_id",
+                json!([account_id, contact_id]),
+            )
+            .await?;
+        }
+        let entries = call(
+            &handle,
+            &mut receiver,
+            next_id(),
+            "get_chatlist_entries",
+            json!([account_id, null, null, null]),
+        )
+        .await?;
+
+        let start = Instant::now();
+        let items = call(
+            &handle,
+            &mut receiver,
+            next_id(),
+            "get_chatlist_items_by_entries",
+            json!([account_id, entries]),
+        )
+        .await?;
+        let elapsed = start.elapsed();
+
+        let items = items.as_object().expect("items are no object");
+        assert_eq!(items.len(), entries.as_array().unwrap().len());
+        assert!(items.len() >= CHAT_COUNT as usize);
+        assert!(items.values().all(|item| item["type"] != "Error"));
+        assert!(
+            elapsed < MAX_DURATION,
+            "loading {} chat list items took {:?}, more than {:?}",
+            CHAT_COUNT,
+            elapsed,
+            MAX_DURATION
+        );
+
+        Ok(())
+    }
+    /// Generates the typescript types that are not part of a method signature,
+    /// so they are not generated by yerpc.
+    #[test]
+    fn generate_extra_ts_bindings() -> anyhow::Result<()> {
+        let mut file = std::fs::File::create("typescript/generated/extra_types.ts")?;
+        let options = DefinitionFileOptions {
+            root_namespace: None,
+            ..DefinitionFileOptions::default()
+        };
+        write_definition_file::<_, (ServerEvent, ErrorData)>(&mut file, options)?;
+        Ok(())
+    }
 }
@@ -1,12 +1,14 @@
 use async_std::path::PathBuf;
+use async_std::sync::Arc;
 use async_std::task;
+use futures::StreamExt;
 use tide::Request;
-use yerpc::RpcHandle;
-use yerpc_tide::yerpc_handler;
+use tide_websockets::{Message, WebSocket, WebSocketConnection};
+use yerpc::{MessageHandle, RpcHandle};
 
 mod api;
 use api::events::event_to_json_rpc_notification;
-use api::{Accounts, CommandApi};
+use api::{batch, Accounts, CommandApi};
 
 #[async_std::main]
 async fn main() -> Result<(), std::io::Error> {
@@ -16,8 +18,15 @@ async fn main() -> Result<(), std::io::Error> {
     let accounts = Accounts::new(PathBuf::from("./accounts")).await.unwrap();
     let state = CommandApi::new(accounts);
 
+    // one loop for all connections, it updates the server state
+    // and feeds the event loops of the connections
+    task::spawn({
+        let state = state.clone();
+        async move { state.run_event_loop().await }
+    });
+
     let mut app = tide::with_state(state.clone());
-    app.at("/ws").get(yerpc_handler(request_handler));
+    app.at("/ws").get(WebSocket::new(request_handler));
 
     state.accounts.read().await.start_io().await;
     app.listen("127.0.0.1:20808").await?;
@@ -26,19 +35,58 @@ async fn main() -> Result<(), std::io::Error> {
 }
 async fn request_handler(
     request: Request<CommandApi>,
-    rpc: RpcHandle,
-) -> anyhow::Result<CommandApi> {
-    let state = request.state().clone();
-    task::spawn(event_loop(state.clone(), rpc));
-    Ok(state)
+    mut stream: WebSocketConnection,
+) -> tide::Result<()> {
+    let state = request.state().for_connection();
+    let (rpc, mut outgoing) = RpcHandle::new();
+    let handle = Arc::new(MessageHandle::new(rpc.clone(), state.clone()));
+    task::spawn(event_loop(state.clone(), rpc.clone()));
+    task::spawn(server_event_loop(state.clone(), rpc));
+
+    let sender = stream.clone();
+    task::spawn(async move {
+        while let Some(message) = outgoing.next().await {
+            let message = serde_json::to_string(&message)?;
+            sender.send_string(message).await?;
+        }
+        Ok::<(), anyhow::Error>(())
+    });
+
+    while let Some(Ok(message)) = stream.next().await {
+        let input = match message {
+            Message::Text(input) => input,
+            // pings are answered by the websocket library and a close frame ends the stream
+            _ => continue,
+        };
+        let (handle, state, sender) = (handle.clone(), state.clone(), stream.clone());
+        // handle every message in its own task, so long running requests don't block others
+        task::spawn(async move {
+            // batches are answered here, single messages through `outgoing`
+            if let Some(response) = batch::handle_message(&handle, &state, &input).await {
+                sender.send_string(response).await?;
+            }
+            Ok::<(), anyhow::Error>(())
+        });
+    }
+    state.close_connection();
+    Ok(())
 }
 
 async fn event_loop(state: CommandApi, rpc: RpcHandle) -> anyhow::Result<()> {
-    let mut events = state.accounts.read().await.get_event_emitter().await;
-    while let Ok(Some(event)) = events.recv().await {
+    let events = state.subscribe_account_events();
+    while let Ok(event) = events.recv().await {
         // log::debug!("event {:?}", event);
         let event = event_to_json_rpc_notification(event);
         rpc.notify("event", Some(event)).await?;
     }
     Ok(())
 }
+
+async fn server_event_loop(state: CommandApi, rpc: RpcHandle) -> anyhow::Result<()> {
+    let events = state.subscribe_server_events();
+    while let Ok(event) = events.recv().await {
+        rpc.notify("server_event", Some(serde_json::to_value(event)?))
+            .await?;
+    }
+    Ok(())
+}
@@ -83,7 +83,8 @@ async function run() {
         messageIds
       );
       for (const [_messageId, message] of Object.entries(messages)) {
-        write($main, `<p>${message.text}</p>`);
+        if (message.type === "Message") write($main, `<p>${message.text}</p>`);
+        else write($main, `<p>${message.error}</p>`);
       }
     }
   }
@@ -26,6 +26,46 @@ export class RawClient {
     return (this._transport.request('get_system_info', [] as RPC.Params)) as Promise<Record<string,string>>;
   }
 
+  /**
+   * Get all config keys that are meant to be set by the user,
+   * with their type, default value and description.
+   */
+  public getConfigKeys(): Promise<(T.ConfigKeyInfo)[]> {
+    return (this._transport.request('get_config_keys', [] as RPC.Params)) as Promise<(T.ConfigKeyInfo)[]>;
+  }
+
+  /**
+   * Hint that the network is available again,
+   * so all accounts try to connect now instead of waiting for their next retry.
+   */
+  public maybeNetwork(): Promise<null> {
+    return (this._transport.request('maybe_network', [] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Start fetching and sending messages for all accounts.
+   */
+  public startIoForAllAccounts(): Promise<null> {
+    return (this._transport.request('start_io_for_all_accounts', [] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Stop fetching and sending messages for all accounts, for example while the app is in the background.
+   */
+  public stopIoForAllAccounts(): Promise<null> {
+    return (this._transport.request('stop_io_for_all_accounts', [] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Set translations for the stock strings of the core, by stock string id,
+   * so summaries, info messages and device messages are localized.
+   *
+   * The translations are set for all accounts, including the ones added later.
+   */
+  public setStockStrings(strings: Record<T.U32,string>): Promise<null> {
+    return (this._transport.request('set_stock_strings', [strings] as RPC.Params)) as Promise<null>;
+  }
+
 
   public addAccount(): Promise<T.U32> {
     return (this._transport.request('add_account', [] as RPC.Params)) as Promise<T.U32>;
@@ -57,6 +97,29 @@ export class RawClient {
     return (this._transport.request('get_selected_account_id', [] as RPC.Params)) as Promise<(T.U32|null)>;
   }
 
+  /**
+   * Get the number of fresh messages of every account, for example for a badge counter.
+   */
+  public getFreshMsgCounts(): Promise<Record<T.U32,T.Usize>> {
+    return (this._transport.request('get_fresh_msg_counts', [] as RPC.Params)) as Promise<Record<T.U32,T.Usize>>;
+  }
+
+  /**
+   * Get the worst connectivity of all configured accounts, for a global status indicator.
+   *
+   * Returns null if there is no configured account.
+   */
+  public getOverallConnectivity(): Promise<(T.Connectivity|null)> {
+    return (this._transport.request('get_overall_connectivity', [] as RPC.Params)) as Promise<(T.Connectivity|null)>;
+  }
+
+  /**
+   * Get the ids of the fresh messages of every account, newest first.
+   */
+  public getFreshMsgsOfAllAccounts(): Promise<Record<T.U32,(T.U32)[]>> {
+    return (this._transport.request('get_fresh_msgs_of_all_accounts', [] as RPC.Params)) as Promise<Record<T.U32,(T.U32)[]>>;
+  }
+
   /**
    * Get a list of all configured accounts.
    */
@@ -71,6 +134,36 @@ export class RawClient {
     return (this._transport.request('get_account_info', [accountId] as RPC.Params)) as Promise<T.Account>;
   }
 
+  /**
+   * Set the own avatar to the image at the given path, null removes the avatar.
+   *
+   * The image is copied into the blobdir and scaled down, so the file may be removed afterwards.
+   * Returns the updated account.
+   */
+  public setSelfAvatar(accountId: T.U32, path: (string|null)): Promise<T.Account> {
+    return (this._transport.request('set_self_avatar', [accountId, path] as RPC.Params)) as Promise<T.Account>;
+  }
+
+  /**
+   * Set the own avatar to an image uploaded as base64 encoded data,
+   * the file name is used to detect the image type.
+   *
+   * Returns the updated account.
+   */
+  public setSelfAvatarFromBase64(accountId: T.U32, fileName: string, data: string): Promise<T.Account> {
+    return (this._transport.request('set_self_avatar_from_base64', [accountId, fileName, data] as RPC.Params)) as Promise<T.Account>;
+  }
+
+  /**
+   * Set the own display name and status (signature) at once.
+   * Only the values that are not null are changed, an empty string removes them.
+   *
+   * Returns the updated account.
+   */
+  public setSelfProfile(accountId: T.U32, displayName: (string|null), status: (string|null)): Promise<T.Account> {
+    return (this._transport.request('set_self_profile', [accountId, displayName, status] as RPC.Params)) as Promise<T.Account>;
+  }
+
   /**
    * Returns provider for the given domain.
    *
@@ -85,6 +178,20 @@ export class RawClient {
     return (this._transport.request('get_provider_info', [accountId, email] as RPC.Params)) as Promise<(T.ProviderInfo|null)>;
   }
 
+  /**
+   * Get the number of fresh messages of an account.
+   */
+  public getFreshMsgCount(accountId: T.U32): Promise<T.Usize> {
+    return (this._transport.request('get_fresh_msg_count', [accountId] as RPC.Params)) as Promise<T.Usize>;
+  }
+
+  /**
+   * Get the ids of the fresh messages of an account, newest first.
+   */
+  public getFreshMsgs(accountId: T.U32): Promise<(T.U32)[]> {
+    return (this._transport.request('get_fresh_msgs', [accountId] as RPC.Params)) as Promise<(T.U32)[]>;
+  }
+
   /**
    * Checks if the context is already configured.
    */
@@ -97,6 +204,23 @@ export class RawClient {
     return (this._transport.request('get_info', [accountId] as RPC.Params)) as Promise<Record<string,string>>;
   }
 
+  /**
+   * Get the connectivity of an account.
+   */
+  public getConnectivity(accountId: T.U32): Promise<T.Connectivity> {
+    return (this._transport.request('get_connectivity', [accountId] as RPC.Params)) as Promise<T.Connectivity>;
+  }
+
+  /**
+   * Get a detailed connectivity report of an account as html page.
+   *
+   * The report also shows the storage quota of the server. There is no typed quota method,
+   * as the core keeps the quota internal and only puts it into this report.
+   */
+  public getConnectivityHtml(accountId: T.U32): Promise<string> {
+    return (this._transport.request('get_connectivity_html', [accountId] as RPC.Params)) as Promise<string>;
+  }
+
 
   public setConfig(accountId: T.U32, key: string, value: (string|null)): Promise<null> {
     return (this._transport.request('set_config', [accountId, key, value] as RPC.Params)) as Promise<null>;
@@ -117,6 +241,35 @@ export class RawClient {
     return (this._transport.request('batch_get_config', [accountId, keys] as RPC.Params)) as Promise<Record<string,(string|null)>>;
   }
 
+  /**
+   * Get the typed account settings.
+   */
+  public getAccountSettings(accountId: T.U32): Promise<T.AccountSettings> {
+    return (this._transport.request('get_account_settings', [accountId] as RPC.Params)) as Promise<T.AccountSettings>;
+  }
+
+  /**
+   * Change the typed account settings.
+   * Only the fields that are set are changed.
+   */
+  public setAccountSettings(accountId: T.U32, settings: T.AccountSettings): Promise<null> {
+    return (this._transport.request('set_account_settings', [accountId, settings] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Get the videochat instance used for videochat invitations, if one is set.
+   */
+  public getWebrtcInstance(accountId: T.U32): Promise<(T.WebrtcInstance|null)> {
+    return (this._transport.request('get_webrtc_instance', [accountId] as RPC.Params)) as Promise<(T.WebrtcInstance|null)>;
+  }
+
+  /**
+   * Set the videochat instance used for videochat invitations, null removes it.
+   */
+  public setWebrtcInstance(accountId: T.U32, instance: (T.WebrtcInstance|null)): Promise<null> {
+    return (this._transport.request('set_webrtc_instance', [accountId, instance] as RPC.Params)) as Promise<null>;
+  }
+
   /**
    * Configures this account with the currently set parameters.
    * Setup the credential config before calling this.
@@ -125,6 +278,24 @@ export class RawClient {
     return (this._transport.request('configure', [accountId] as RPC.Params)) as Promise<null>;
   }
 
+  /**
+   * Get the url to log in at the email provider of the address using OAuth2.
+   *
+   * After logging in, the provider redirects to `redirect_uri` with the code
+   * that has to be passed to `configure_with_oauth2_code`.
+   * Returns null if the provider does not support OAuth2.
+   */
+  public getOauth2Url(accountId: T.U32, email: string, redirectUri: string): Promise<(string|null)> {
+    return (this._transport.request('get_oauth2_url', [accountId, email, redirectUri] as RPC.Params)) as Promise<(string|null)>;
+  }
+
+  /**
+   * Configures this account with the code that the OAuth2 login redirected to.
+   */
+  public configureWithOauth2Code(accountId: T.U32, email: string, code: string): Promise<null> {
+    return (this._transport.request('configure_with_oauth2_code', [accountId, email, code] as RPC.Params)) as Promise<null>;
+  }
+
   /**
    * Signal an ongoing process to stop.
    */
@@ -132,6 +303,35 @@ export class RawClient {
     return (this._transport.request('stop_ongoing_process', [accountId] as RPC.Params)) as Promise<null>;
   }
 
+  /**
+   * Cancel the long running operation (like `configure`) of this account.
+   *
+   * Cancelling works per account, not per json-rpc request,
+   * but as an account only runs one such operation at a time,
+   * the account id identifies the request to cancel.
+   *
+   * The call that started the operation fails with a `Cancelled` error
+   * once the operation has stopped.
+   * Returns false if there was no operation to cancel.
+   */
+  public cancelOngoingOperation(accountId: T.U32): Promise<boolean> {
+    return (this._transport.request('cancel_ongoing_operation', [accountId] as RPC.Params)) as Promise<boolean>;
+  }
+
+  /**
+   * Start fetching and sending messages for this account.
+   */
+  public startIo(accountId: T.U32): Promise<null> {
+    return (this._transport.request('start_io', [accountId] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Stop fetching and sending messages for this account.
+   */
+  public stopIo(accountId: T.U32): Promise<null> {
+    return (this._transport.request('stop_io', [accountId] as RPC.Params)) as Promise<null>;
+  }
+
 
   public autocryptInitiateKeyTransfer(accountId: T.U32): Promise<string> {
     return (this._transport.request('autocrypt_initiate_key_transfer', [accountId] as RPC.Params)) as Promise<string>;
@@ -152,6 +352,28 @@ export class RawClient {
     return (this._transport.request('get_chatlist_items_by_entries', [accountId, entries] as RPC.Params)) as Promise<Record<T.U32,T.ChatListItemFetchResult>>;
   }
 
+  /**
+   * Subscribe to a chat list, to get its changes pushed
+   * in `ChatListChanged` server events instead of reloading it after every event.
+   *
+   * The arguments are the same as for `get_chatlist_entries`.
+   * Returns the subscription id and the current state of the chat list.
+   *
+   * The subscription is removed when the connection that made it is closed.
+   */
+  public chatlistSubscribe(accountId: T.U32, listFlags: (T.U32|null), queryString: (string|null), queryContactId: (T.U32|null)): Promise<T.ChatListSubscription> {
+    return (this._transport.request('chatlist_subscribe', [accountId, listFlags, queryString, queryContactId] as RPC.Params)) as Promise<T.ChatListSubscription>;
+  }
+
+  /**
+   * Stop getting changes of the chat list.
+   *
+   * Returns false if there was no subscription with this id.
+   */
+  public chatlistUnsubscribe(subscriptionId: T.U32): Promise<boolean> {
+    return (this._transport.request('chatlist_unsubscribe', [subscriptionId] as RPC.Params)) as Promise<boolean>;
+  }
+
 
   public chatlistGetFullChatById(accountId: T.U32, chatId: T.U32): Promise<T.FullChat> {
     return (this._transport.request('chatlist_get_full_chat_by_id', [accountId, chatId] as RPC.Params)) as Promise<T.FullChat>;
@@ -167,6 +389,59 @@ export class RawClient {
     return (this._transport.request('block_chat', [accountId, chatId] as RPC.Params)) as Promise<null>;
   }
 
+  /**
+   * Get the pending contact requests, newest first.
+   *
+   * Accept them with `accept_chat` or block them with `block_chat`.
+   * A request that can't be loaded is returned as error item instead of failing the whole list.
+   */
+  public getContactRequests(accountId: T.U32): Promise<(T.ContactRequestFetchResult)[]> {
+    return (this._transport.request('get_contact_requests', [accountId] as RPC.Params)) as Promise<(T.ContactRequestFetchResult)[]>;
+  }
+
+  /**
+   * Declines a contact request without blocking the requester
+   * by deleting the chat. A new message of the requester starts a new request.
+   */
+  public declineContactRequest(accountId: T.U32, chatId: T.U32): Promise<null> {
+    return (this._transport.request('decline_contact_request', [accountId, chatId] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Marks all messages of the chat as noticed, so they are not fresh anymore.
+   *
+   * In contrast to `markseen_msgs` no read receipts are sent.
+   */
+  public marknoticedChat(accountId: T.U32, chatId: T.U32): Promise<null> {
+    return (this._transport.request('marknoticed_chat', [accountId, chatId] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Send a videochat invitation to the chat, using the instance set with `set_webrtc_instance`.
+   *
+   * Returns the created message.
+   */
+  public sendVideochatInvitation(accountId: T.U32, chatId: T.U32): Promise<T.Message> {
+    return (this._transport.request('send_videochat_invitation', [accountId, chatId] as RPC.Params)) as Promise<T.Message>;
+  }
+
+  /**
+   * Add a message to the device chat, with an optional attachment.
+   *
+   * A message with a `label` is only added once, even if the device chat was deleted meanwhile.
+   * Returns the id of the added message or null if the label was already used.
+   */
+  public addDeviceMessage(accountId: T.U32, label: (string|null), text: (string|null), file: (string|null)): Promise<(T.U32|null)> {
+    return (this._transport.request('add_device_message', [accountId, label, text, file] as RPC.Params)) as Promise<(T.U32|null)>;
+  }
+
+  /**
+   * Check if a device message with this label was already added.
+   */
+  public wasDeviceMessageEverAdded(accountId: T.U32, label: string): Promise<boolean> {
+    return (this._transport.request('was_device_message_ever_added', [accountId, label] as RPC.Params)) as Promise<boolean>;
+  }
+
 
   public messageListGetMessageIds(accountId: T.U32, chatId: T.U32, flags: T.U32): Promise<(T.U32)[]> {
     return (this._transport.request('message_list_get_message_ids', [accountId, chatId, flags] as RPC.Params)) as Promise<(T.U32)[]>;
@@ -178,8 +453,55 @@ export class RawClient {
   }
 
 
-  public messageGetMessages(accountId: T.U32, messageIds: (T.U32)[]): Promise<Record<T.U32,T.Message>> {
-    return (this._transport.request('message_get_messages', [accountId, messageIds] as RPC.Params)) as Promise<Record<T.U32,T.Message>>;
+  public messageGetMessages(accountId: T.U32, messageIds: (T.U32)[]): Promise<Record<T.U32,T.MessageFetchResult>> {
+    return (this._transport.request('message_get_messages', [accountId, messageIds] as RPC.Params)) as Promise<Record<T.U32,T.MessageFetchResult>>;
+  }
+
+  /**
+   * Get the messages for rendering a message list, see `MessageListItem`.
+   *
+   * Their senders are loaded only once and returned in `contacts`.
+   */
+  public messageListGetItems(accountId: T.U32, messageIds: (T.U32)[]): Promise<T.MessageListItems> {
+    return (this._transport.request('message_list_get_items', [accountId, messageIds] as RPC.Params)) as Promise<T.MessageListItems>;
+  }
+
+  /**
+   * Marks the messages as seen, call this when they are shown to the user.
+   *
+   * This sends read receipts to the senders if enabled
+   * and emits `MsgsNoticed` so the fresh message counters update.
+   */
+  public markseenMsgs(accountId: T.U32, messageIds: (T.U32)[]): Promise<null> {
+    return (this._transport.request('markseen_msgs', [accountId, messageIds] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Download the rest of a partially downloaded message, see `download_state`.
+   *
+   * This only starts the download, a `MsgsChanged` event is emitted when it is done.
+   */
+  public downloadFullMessage(accountId: T.U32, messageId: T.U32): Promise<null> {
+    return (this._transport.request('download_full_message', [accountId, messageId] as RPC.Params)) as Promise<null>;
+  }
+
+  /**
+   * Get the ids of all messages with one of the given viewtypes (at most three),
+   * oldest first.
+   *
+   * If `chat_id` is null, the media of all chats is returned.
+   */
+  public chatGetMedia(accountId: T.U32, chatId: (T.U32|null), messageTypes: (T.MessageViewtype)[]): Promise<(T.U32)[]> {
+    return (this._transport.request('chat_get_media', [accountId, chatId, messageTypes] as RPC.Params)) as Promise<(T.U32)[]>;
+  }
+
+  /**
+   * Get the ids of the previous and of the next message with one of the
+   * given viewtypes (at most three) in the chat of the message,
+   * to navigate through the media of a chat.
+   */
+  public chatGetNeighboringMedia(accountId: T.U32, messageId: T.U32, messageTypes: (T.MessageViewtype)[]): Promise<[(T.U32|null),(T.U32|null)]> {
+    return (this._transport.request('chat_get_neighboring_media', [accountId, messageId, messageTypes] as RPC.Params)) as Promise<[(T.U32|null),(T.U32|null)]>;
   }
 
   /**
@@ -216,8 +538,8 @@ export class RawClient {
   }
 
 
-  public contactsGetBlocked(accountId: T.U32): Promise<(T.Contact)[]> {
-    return (this._transport.request('contacts_get_blocked', [accountId] as RPC.Params)) as Promise<(T.Contact)[]>;
+  public contactsGetBlocked(accountId: T.U32): Promise<(T.ContactFetchResult)[]> {
+    return (this._transport.request('contacts_get_blocked', [accountId] as RPC.Params)) as Promise<(T.ContactFetchResult)[]>;
   }
 
 
@@ -229,13 +551,74 @@ export class RawClient {
    * Get a list of contacts.
    * (formerly called getContacts2 in desktop)
    */
-  public contactsGetContacts(accountId: T.U32, listFlags: T.U32, query: (string|null)): Promise<(T.Contact)[]> {
-    return (this._transport.request('contacts_get_contacts', [accountId, listFlags, query] as RPC.Params)) as Promise<(T.Contact)[]>;
+  public contactsGetContacts(accountId: T.U32, listFlags: T.U32, query: (string|null)): Promise<(T.ContactFetchResult)[]> {
+    return (this._transport.request('contacts_get_contacts', [accountId, listFlags, query] as RPC.Params)) as Promise<(T.ContactFetchResult)[]>;
+  }
+
+
+  public contactsGetContactsByIds(accountId: T.U32, ids: (T.U32)[]): Promise<Record<T.U32,T.ContactFetchResult>> {
+    return (this._transport.request('contacts_get_contacts_by_ids', [accountId, ids] as RPC.Params)) as Promise<Record<T.U32,T.ContactFetchResult>>;
+  }
+
+  /**
+   * Send a status update to all instances of the webxdc app, including this one.
+   *
+   * `description` is shown in the chat, it may be empty.
+   */
+  public webxdcSendStatusUpdate(accountId: T.U32, instanceMsgId: T.U32, updateStr: string, description: string): Promise<null> {
+    return (this._transport.request('webxdc_send_status_update', [accountId, instanceMsgId, updateStr, description] as RPC.Params)) as Promise<null>;
   }
 
+  /**
+   * Get the status updates of the webxdc app with a serial greater than `last_known_serial`,
+   * as json array string.
+   *
+   * Pass 0 to get all updates. Call this after a `WEBXDC_STATUS_UPDATE` event
+   * with the last serial that was received.
+   */
+  public webxdcGetStatusUpdates(accountId: T.U32, instanceMsgId: T.U32, lastKnownSerial: T.U32): Promise<string> {
+    return (this._transport.request('webxdc_get_status_updates', [accountId, instanceMsgId, lastKnownSerial] as RPC.Params)) as Promise<string>;
+  }
+
+  /**
+   * Get name, icon and summary of a webxdc message.
+   */
+  public messageGetWebxdcInfo(accountId: T.U32, instanceMsgId: T.U32): Promise<T.WebxdcMessageInfo> {
+    return (this._transport.request('message_get_webxdc_info', [accountId, instanceMsgId] as RPC.Params)) as Promise<T.WebxdcMessageInfo>;
+  }
+
+  /**
+   * Get a file from the .xdc archive of a webxdc message, base64 encoded.
+   *
+   * `path` is relative to the root of the archive, like `index.html`.
+   */
+  public webxdcGetBlob(accountId: T.U32, instanceMsgId: T.U32, path: string): Promise<string> {
+    return (this._transport.request('webxdc_get_blob', [accountId, instanceMsgId, path] as RPC.Params)) as Promise<string>;
+  }
 
-  public contactsGetContactsByIds(accountId: T.U32, ids: (T.U32)[]): Promise<Record<T.U32,T.Contact>> {
-    return (this._transport.request('contacts_get_contacts_by_ids', [accountId, ids] as RPC.Params)) as Promise<Record<T.U32,T.Contact>>;
+  /**
+   * Get the stickers of the account: the paths of the sticker images by pack name.
+   */
+  public stickersGetStickers(accountId: T.U32): Promise<Record<string,(string)[]>> {
+    return (this._transport.request('stickers_get_stickers', [accountId] as RPC.Params)) as Promise<Record<string,(string)[]>>;
+  }
+
+  /**
+   * Copy an image into a sticker pack of the account, the pack is created if needed.
+   *
+   * Returns the path of the new sticker.
+   */
+  public stickersImportSticker(accountId: T.U32, pack: string, path: string): Promise<string> {
+    return (this._transport.request('stickers_import_sticker', [accountId, pack, path] as RPC.Params)) as Promise<string>;
+  }
+
+  /**
+   * Send a sticker, for example one returned by `stickers_get_stickers`.
+   *
+   * Returns the id of the sent message.
+   */
+  public sendSticker(accountId: T.U32, chatId: T.U32, stickerPath: string): Promise<T.U32> {
+    return (this._transport.request('send_sticker', [accountId, chatId, stickerPath] as RPC.Params)) as Promise<T.U32>;
   }
 
   /**
@@ -4,6 +4,7 @@ import { RawClient } from "../generated/client.js";
 import { WebsocketTransport, BaseTransport, Request } from "yerpc";
 import { eventIdToName } from "./events.js";
 import { TinyEmitter } from "tiny-emitter";
+import { ServerEvent, ServerEvents } from "./server_events.js";
 
 export type EventNames = ReturnType<typeof eventIdToName> | "ALL";
 export type WireEvent = {
@@ -14,12 +15,40 @@ export type WireEvent = {
 };
 export type DeltachatEvent = WireEvent & { name: EventNames };
 export type Events = Record<EventNames, (event: DeltachatEvent) => void>;
+/**
+ * Private methods of the yerpc transport that are needed for batches.
+ * yerpc is pinned to an exact version in package.json because of them,
+ * check they still exist before updating it.
+ *
+ * Other transports may not have them, they can be used for everything but `batch()`.
+ */
+type TransportInternals = {
+  _send(message: RPC.Message | RPC.Message[]): void;
+  _onmessage(message: RPC.Message | RPC.Message[]): void;
+};
+
+function getTransportInternals(transport: BaseTransport): TransportInternals {
+  const internals = transport as unknown as Partial<TransportInternals>;
+  if (
+    typeof internals._send !== "function" ||
+    typeof internals._onmessage !== "function"
+  ) {
+    throw new Error("the yerpc transport doesn't support batches");
+  }
+  return internals as TransportInternals;
+}
+export type ConfigureProgress = Extract<
+  ServerEvent,
+  { type: "ConfigureProgress" }
+>;
 
 export class BaseDeltachat<
   Transport extends BaseTransport
 > extends TinyEmitter<Events> {
   rpc: RawClient;
   account?: T.Account;
+  /** Events of the server itself, not belonging to a single account */
+  serverEvents = new TinyEmitter<ServerEvents>();
   constructor(protected transport: Transport) {
     super();
     this.rpc = new RawClient(this.transport);
@@ -36,6 +65,10 @@ export class BaseDeltachat<
           this.contextEmitters[params.contextId].emit(name, event);
           this.contextEmitters[params.contextId].emit("ALL", event);
         }
+      } else if (method === "server_event") {
+        const event = request.params! as ServerEvent;
+        this.serverEvents.emit(event.type, event);
+        this.serverEvents.emit("ALL", event);
       }
     });
   }
@@ -49,6 +82,86 @@ export class BaseDeltachat<
     return await this.rpc.getAllAccounts();
   }
 
+  /**
+   * Configures the account and reports its progress until configuring is done.
+   * The server only sends `ConfigureProgress` events while a configure runs and
+   * there can only be one configure per account, so all progress events of
+   * the account belong to this call.
+   */
+  async configure(
+    accountId: number,
+    onProgress?: (progress: ConfigureProgress) => void
+  ) {
+    const listener = (event: ServerEvent) => {
+      if (event.type === "ConfigureProgress" && event.accountId === accountId) {
+        onProgress?.(event);
+      }
+    };
+    this.serverEvents.on("ConfigureProgress", listener);
+    try {
+      await this.rpc.configure(accountId);
+    } finally {
+      this.serverEvents.off("ConfigureProgress", listener);
+    }
+  }
+
+  /**
+   * Sends all requests made on `rpc` inside of `calls` in one json-rpc batch,
+   * so they only need one round trip and are executed concurrently.
+   *
+   * ```ts
+   * const [chat, contacts] = await dc.batch((rpc) => [
+   *   rpc.chatlistGetFullChatById(accountId, chatId),
+   *   rpc.contactsGetContactsByIds(accountId, contactIds),
+   * ]);
+   * ```
+   *
+   * Like with single requests, the returned promise is rejected
+   * if one of the requests fails.
+   * Throws if the transport doesn't support batches.
+   */
+  async batch<T extends readonly Promise<unknown>[]>(
+    calls: (rpc: RawClient) => [...T]
+  ): Promise<{ [K in keyof T]: Awaited<T[K]> }> {
+    const transportInternals = this.getBatchTransport();
+    const send = transportInternals._send;
+    const requests: RPC.Message[] = [];
+    transportInternals._send = (message) =>
+      requests.push(message as RPC.Message);
+    let promises: [...T];
+    try {
+      promises = calls(this.rpc);
+    } finally {
+      transportInternals._send = send;
+    }
+    if (requests.length > 0) transportInternals._send(requests);
+    return (await Promise.all(promises)) as {
+      [K in keyof T]: Awaited<T[K]>;
+    };
+  }
+
+  private batchTransport?: TransportInternals;
+
+  /**
+   * Prepares the transport for batches on the first call of `batch()`,
+   * throws if the transport doesn't support them.
+   */
+  private getBatchTransport(): TransportInternals {
+    if (!this.batchTransport) {
+      const transportInternals = getTransportInternals(this.transport);
+      // the responses of a batch arrive as one array, pass them on one by one
+      const onmessage = transportInternals._onmessage.bind(this.transport);
+      transportInternals._onmessage = (
+        message: RPC.Message | RPC.Message[]
+      ) => {
+        if (Array.isArray(message)) message.forEach(onmessage);
+        else onmessage(message);
+      };
+      this.batchTransport = transportInternals;
+    }
+    return this.batchTransport;
+  }
+
   private contextEmitters: TinyEmitter<Events>[] = [];
 
   getContextEvents(account_id: number) {
@@ -0,0 +1,40 @@
+import { ErrorData } from "../generated/extra_types.js";
+
+export type { ErrorData } from "../generated/extra_types.js";
+
+// The error codes are constants and not types, so they are NOT generated
+// Keep in sync with the error codes in src/api/error.rs
+
+export enum ErrorCode {
+  GENERIC = -1,
+  ACCOUNT_NOT_FOUND = 100,
+  CHAT_NOT_FOUND = 101,
+  CONTACT_NOT_FOUND = 102,
+  MESSAGE_NOT_FOUND = 103,
+  INVALID_ADDRESS = 200,
+  UNKNOWN_CONFIG_KEY = 201,
+  NOT_WEBXDC_MESSAGE = 202,
+  NOT_CONTACT_REQUEST = 203,
+  UNKNOWN_STOCK_STRING = 204,
+  NOT_CONFIGURED = 300,
+  IO_BUSY = 301,
+  CANCELLED = 302,
+  WEBRTC_INSTANCE_NOT_SET = 303,
+  CONFIGURE_FAILED = 400,
+  OAUTH2_FAILED = 401,
+}
+
+export type DeltachatError = {
+  code: ErrorCode;
+  message: string;
+  data?: ErrorData;
+};
+
+export function isDeltachatError(error: unknown): error is DeltachatError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as DeltachatError).code === "number" &&
+    typeof (error as DeltachatError).message === "string"
+  );
+}
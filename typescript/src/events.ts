@@ -29,6 +29,7 @@ export enum Event_TypeID {
   SECUREJOIN_INVITER_PROGRESS = 2060,
   SECUREJOIN_JOINER_PROGRESS = 2061,
   CONNECTIVITY_CHANGED = 2100,
+  WEBXDC_STATUS_UPDATE = 2120,
 }
 
 export function eventIdToName(
@@ -4,3 +4,5 @@ export { RawClient } from "../generated/client.js";
 export * from "./client.js";
 export * as yerpc from "yerpc";
 export * from "./events.js";
+export * from "./errors.js";
+export * from "./server_events.js";
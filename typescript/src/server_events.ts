@@ -0,0 +1,9 @@
+import { ServerEvent } from "../generated/extra_types.js";
+
+export type { ServerEvent, ChatListChange } from "../generated/extra_types.js";
+
+export type ServerEventNames = ServerEvent["type"] | "ALL";
+export type ServerEvents = Record<
+  ServerEventNames,
+  (event: ServerEvent) => void
+>;
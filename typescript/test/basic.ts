@@ -1,8 +1,11 @@
 import { strictEqual } from "assert";
+import { mkdtemp, writeFile } from "fs/promises";
+import { tmpdir } from "os";
+import { join } from "path";
 import chai, { assert, expect } from "chai";
 import chaiAsPromised from "chai-as-promised";
 chai.use(chaiAsPromised);
-import { Deltachat } from "../dist/deltachat.js";
+import { Deltachat, ErrorCode, ServerEvent } from "../dist/deltachat.js";
 
 import {
   CMD_API_Server_Handle,
@@ -98,7 +101,10 @@ describe("basic tests", () => {
       await dc.rpc.contactsBlock(acc, contactId);
       expect((await dc.rpc.contactsGetContact(acc, contactId)).is_blocked).to.be
         .true;
-      expect(await dc.rpc.contactsGetBlocked(acc)).to.have.length(1);
+      const blocked = await dc.rpc.contactsGetBlocked(acc);
+      expect(blocked).to.have.length(1);
+      assert(blocked[0].type === "Contact");
+      expect(blocked[0].id).to.equal(contactId);
       await dc.rpc.contactsUnblock(acc, contactId);
       expect((await dc.rpc.contactsGetContact(acc, contactId)).is_blocked).to.be
         .false;
@@ -154,5 +160,536 @@ describe("basic tests", () => {
       const retrieved = await dc.rpc.batchGetConfig(acc, Object.keys(config));
       expect(retrieved).to.deep.equal(config);
     });
+    it("list config keys", async function () {
+      const keys = await dc.rpc.getConfigKeys();
+      const mdns = keys.find(({ key }) => key === "mdns_enabled");
+      expect(mdns?.value_type).to.deep.equal({ type: "Boolean" });
+      expect(mdns?.default).to.equal("1");
+      const showEmails = keys.find(({ key }) => key === "show_emails");
+      assert(showEmails?.value_type.type === "Enum");
+      const values = showEmails.value_type.variants.map(({ value }) => value);
+      expect(values).to.include(showEmails.default);
+    });
+    it("set and retrive typed settings", async function () {
+      await dc.rpc.setAccountSettings(acc, {
+        mdns_enabled: false,
+        show_emails: "All",
+        media_quality: "Worse",
+        delete_server_after: 1,
+        download_limit: 160000,
+      });
+      const settings = await dc.rpc.getAccountSettings(acc);
+      expect(settings.mdns_enabled).to.be.false;
+      expect(settings.show_emails).to.equal("All");
+      expect(settings.media_quality).to.equal("Worse");
+      expect(settings.delete_server_after).to.equal(1);
+      expect(settings.delete_device_after).to.equal(0);
+      expect(settings.download_limit).to.equal(160000);
+      assert((await dc.rpc.getConfig(acc, "show_emails")) == "2");
+    });
+    it("out of range settings fall back to the default", async function () {
+      await dc.rpc.setConfig(acc, "media_quality", "7");
+      const settings = await dc.rpc.getAccountSettings(acc);
+      expect(settings.media_quality).to.equal("Balanced");
+    });
+  });
+
+  describe("error codes", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("unknown account", async function () {
+      const error = await dc.rpc.getAccountInfo(9999).catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.ACCOUNT_NOT_FOUND);
+      expect(error.data).to.deep.equal({
+        kind: "AccountNotFound",
+        accountId: 9999,
+      });
+    });
+    it("invalid email address", async function () {
+      const error = await dc.rpc
+        .contactsCreateContact(acc, "invalid", null)
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.INVALID_ADDRESS);
+    });
+    it("unknown config key", async function () {
+      const error = await dc.rpc.getConfig(acc, "invalid_key").catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.UNKNOWN_CONFIG_KEY);
+      expect(error.data).to.deep.equal({
+        kind: "UnknownConfigKey",
+        key: "invalid_key",
+      });
+    });
+    it("not configured", async function () {
+      const error = await dc.rpc
+        .autocryptInitiateKeyTransfer(acc)
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.NOT_CONFIGURED);
+    });
+    it("configure without address", async function () {
+      const error = await dc.configure(acc).catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.CONFIGURE_FAILED);
+      expect(error.data).to.have.all.keys("kind", "accountId", "reason");
+    });
+  });
+
+  describe("cancellation", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("nothing to cancel", async function () {
+      expect(await dc.rpc.cancelOngoingOperation(acc)).to.be.false;
+    });
+  });
+
+  describe("oauth2", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("get oauth2 url", async function () {
+      const url = await dc.rpc.getOauth2Url(
+        acc,
+        "example@gmail.com",
+        "chat.delta:/com.b44t.messenger"
+      );
+      expect(url).to.be.a("string").and.contain("accounts.google.com");
+    });
+    it("provider requires oauth2", async function () {
+      const info = await dc.rpc.getProviderInfo(acc, "example@gmail.com");
+      expect(info?.oauth2_authorizer).to.equal("Gmail");
+    });
+    it("provider info contains servers", async function () {
+      const info = await dc.rpc.getProviderInfo(acc, "example@gmail.com");
+      expect(info?.id).to.equal("gmail");
+      const imap = info?.servers.find(({ protocol }) => protocol === "Imap");
+      expect(imap?.hostname).to.equal("imap.gmail.com");
+      expect(imap?.port).to.equal(993);
+      expect(imap?.socket).to.equal("Ssl");
+    });
+  });
+
+  describe("chat media", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("no media in new account", async function () {
+      expect(
+        await dc.rpc.chatGetMedia(acc, null, ["Image", "Gif", "Video"])
+      ).to.have.length(0);
+    });
+    it("requires one to three message types", async function () {
+      await expect(dc.rpc.chatGetMedia(acc, null, [])).to.be.eventually
+        .rejected;
+      await expect(
+        dc.rpc.chatGetMedia(acc, null, ["Image", "Gif", "Video", "File"])
+      ).to.be.eventually.rejected;
+    });
+  });
+
+  describe("fresh messages", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("no fresh messages in new account", async function () {
+      expect(await dc.rpc.getFreshMsgCount(acc)).to.equal(0);
+      expect(await dc.rpc.getFreshMsgs(acc)).to.have.length(0);
+    });
+    it("counts of all accounts", async function () {
+      const counts = await dc.rpc.getFreshMsgCounts();
+      expect(counts[acc]).to.equal(0);
+      const fresh = await dc.rpc.getFreshMsgsOfAllAccounts();
+      expect(fresh[acc]).to.deep.equal([]);
+    });
+  });
+
+  describe("seen state", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("mark no messages as seen", async function () {
+      await dc.rpc.markseenMsgs(acc, []);
+      expect(await dc.rpc.getFreshMsgCount(acc)).to.equal(0);
+    });
+  });
+
+  describe("contact requests", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("no requests in new account", async function () {
+      expect(await dc.rpc.getContactRequests(acc)).to.have.length(0);
+    });
+    it("decline unknown chat", async function () {
+      const error = await dc.rpc
+        .declineContactRequest(acc, 9999)
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.CHAT_NOT_FOUND);
+    });
+  });
+
+  describe("chat list subscription", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("pushes inserted chats", async function () {
+      const { subscriptionId, entries } = await dc.rpc.chatlistSubscribe(
+        acc,
+        null,
+        null,
+        null
+      );
+      expect(entries).to.have.length(0);
+      const changed = new Promise<ServerEvent>((resolve) =>
+        dc.serverEvents.on("ChatListChanged", (event) => {
+          if (
+            event.type === "ChatListChanged" &&
+            event.subscriptionId === subscriptionId
+          ) {
+            resolve(event);
+          }
+        })
+      );
+      const contactId = await dc.rpc.contactsCreateContact(
+        acc,
+        "subscribed@delta.chat",
+        null
+      );
+      const chatId = await dc.rpc.contactsCreateChatByContactId(
+        acc,
+        contactId
+      );
+      const event = await changed;
+      assert(event.type === "ChatListChanged");
+      const inserted = event.changes.find(({ type }) => type === "Inserted");
+      expect(inserted).to.include({ index: 0, chatId });
+      expect(await dc.rpc.chatlistUnsubscribe(subscriptionId)).to.be.true;
+      expect(await dc.rpc.chatlistUnsubscribe(subscriptionId)).to.be.false;
+    });
+  });
+
+  describe("message list items", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("empty list", async function () {
+      expect(await dc.rpc.messageListGetItems(acc, [])).to.deep.equal({
+        messages: {},
+        contacts: {},
+      });
+    });
+    it("unknown message", async function () {
+      const { messages } = await dc.rpc.messageListGetItems(acc, [9999]);
+      expect(messages[9999]).to.include({ type: "Error", id: 9999 });
+    });
+  });
+
+  describe("batch getters", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("unknown message doesn't fail the batch", async function () {
+      const messages = await dc.rpc.messageGetMessages(acc, [9999]);
+      expect(messages[9999]).to.include({ type: "Error", id: 9999 });
+    });
+    it("unknown contact doesn't fail the batch", async function () {
+      const contactId = await dc.rpc.contactsCreateContact(
+        acc,
+        "batch@delta.chat",
+        null
+      );
+      const contacts = await dc.rpc.contactsGetContactsByIds(acc, [
+        contactId,
+        9999,
+      ]);
+      expect(contacts[contactId]).to.include({
+        type: "Contact",
+        address: "batch@delta.chat",
+      });
+      expect(contacts[9999]).to.include({ type: "Error", id: 9999 });
+    });
+  });
+
+  describe("batch requests", function () {
+    it("returns the results in order", async function () {
+      const [valid, invalid, systemInfo] = await dc.batch((rpc) => [
+        rpc.checkEmailValidity("email@example.com"),
+        rpc.checkEmailValidity("email@"),
+        rpc.getSystemInfo(),
+      ]);
+      expect(valid).to.be.true;
+      expect(invalid).to.be.false;
+      expect(systemInfo).to.contain.keys(["deltachat_core_version"]);
+    });
+    it("rejects if a request fails", async function () {
+      const error = await dc
+        .batch((rpc) => [rpc.getAccountInfo(9999), rpc.getAllAccountIds()])
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.ACCOUNT_NOT_FOUND);
+    });
+    it("single requests still work after a batch", async function () {
+      await dc.batch((rpc) => [rpc.getAllAccountIds()]);
+      expect(await dc.rpc.checkEmailValidity("email@example.com")).to.be.true;
+    });
+  });
+
+  describe("stickers", function () {
+    let acc: number;
+    let imagePath: string;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+      const dir = await mkdtemp(join(tmpdir(), "stickers"));
+      imagePath = join(dir, "smile.png");
+      await writeFile(imagePath, "not really a png");
+    });
+    it("no stickers in new account", async function () {
+      expect(await dc.rpc.stickersGetStickers(acc)).to.deep.equal({});
+    });
+    it("import sticker", async function () {
+      const path = await dc.rpc.stickersImportSticker(acc, "faces", imagePath);
+      const secondPath = await dc.rpc.stickersImportSticker(
+        acc,
+        "faces",
+        imagePath
+      );
+      expect(secondPath).to.not.equal(path);
+      const stickers = await dc.rpc.stickersGetStickers(acc);
+      expect(stickers.faces).to.have.members([path, secondPath]);
+    });
+    it("rejects invalid pack names", async function () {
+      await expect(dc.rpc.stickersImportSticker(acc, "../faces", imagePath)).to
+        .be.eventually.rejected;
+    });
+  });
+
+  describe("videochat", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("set and retrieve webrtc instance", async function () {
+      expect(await dc.rpc.getWebrtcInstance(acc)).to.be.null;
+      const instance = {
+        videochat_type: "Jitsi" as const,
+        url: "https://meet.jit.si/$ROOM",
+      };
+      await dc.rpc.setWebrtcInstance(acc, instance);
+      expect(await dc.rpc.getWebrtcInstance(acc)).to.deep.equal(instance);
+      assert(
+        (await dc.rpc.getConfig(acc, "webrtc_instance")) ==
+          "jitsi:https://meet.jit.si/$ROOM"
+      );
+      await dc.rpc.setWebrtcInstance(acc, null);
+      expect(await dc.rpc.getWebrtcInstance(acc)).to.be.null;
+    });
+    it("rejects invalid urls", async function () {
+      await expect(
+        dc.rpc.setWebrtcInstance(acc, {
+          videochat_type: "BasicWebrtc",
+          url: "meet.example.org",
+        })
+      ).to.be.eventually.rejected;
+    });
+  });
+
+  describe("device messages", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("add labeled device message once", async function () {
+      expect(await dc.rpc.wasDeviceMessageEverAdded(acc, "release-1.0")).to.be
+        .false;
+      const messageId = await dc.rpc.addDeviceMessage(
+        acc,
+        "release-1.0",
+        "What's new",
+        null
+      );
+      expect(messageId).to.be.a("number");
+      const message = await dc.rpc.messageGetMessage(acc, messageId!);
+      expect(message.text).to.equal("What's new");
+      expect(message.download_state).to.equal("Done");
+      const chat = await dc.rpc.chatlistGetFullChatById(acc, message.chat_id);
+      expect(chat.is_device_chat).to.be.true;
+
+      expect(
+        await dc.rpc.addDeviceMessage(acc, "release-1.0", "again", null)
+      ).to.be.null;
+      expect(await dc.rpc.wasDeviceMessageEverAdded(acc, "release-1.0")).to.be
+        .true;
+    });
+  });
+
+  describe("connectivity", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("unconfigured account is not connected", async function () {
+      expect(await dc.rpc.getConnectivity(acc)).to.equal("NotConnected");
+      expect(await dc.rpc.getConnectivityHtml(acc)).to.be.a("string");
+    });
+    it("no overall connectivity without accounts", async function () {
+      expect(await dc.rpc.getOverallConnectivity()).to.be.null;
+    });
+  });
+
+  describe("io", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("start io requires a configured account", async function () {
+      const error = await dc.rpc.startIo(acc).catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.NOT_CONFIGURED);
+    });
+    it("stop io and network hint", async function () {
+      await dc.rpc.stopIo(acc);
+      await dc.rpc.stopIoForAllAccounts();
+      await dc.rpc.maybeNetwork();
+      await dc.rpc.startIoForAllAccounts();
+    });
+  });
+
+  describe("self profile", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("set display name and status", async function () {
+      await dc.rpc.setSelfProfile(acc, "Alice", "Sent from my phone");
+      expect(await dc.rpc.getConfig(acc, "displayname")).to.equal("Alice");
+      expect(await dc.rpc.getConfig(acc, "selfstatus")).to.equal(
+        "Sent from my phone"
+      );
+      await dc.rpc.setSelfProfile(acc, null, "");
+      expect(await dc.rpc.getConfig(acc, "displayname")).to.equal("Alice");
+    });
+    it("set and remove avatar", async function () {
+      // 1x1 pixel png
+      const image =
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+      await dc.rpc.setSelfAvatarFromBase64(acc, "avatar.png", image);
+      expect(await dc.rpc.getConfig(acc, "selfavatar")).to.not.be.null;
+      await dc.rpc.setSelfAvatar(acc, null);
+      expect(await dc.rpc.getConfig(acc, "selfavatar")).to.be.null;
+    });
+  });
+
+  describe("stock strings", function () {
+    it("set translations for all accounts", async function () {
+      // 1 is the summary of a chat without messages
+      await dc.rpc.setStockStrings({ 1: "Keine Nachrichten." });
+      const acc = await dc.rpc.addAccount();
+      const contactId = await dc.rpc.contactsCreateContact(
+        acc,
+        "translated@delta.chat",
+        null
+      );
+      await dc.rpc.contactsCreateChatByContactId(acc, contactId);
+      const entries = await dc.rpc.getChatlistEntries(acc, null, null, null);
+      const items = await dc.rpc.getChatlistItemsByEntries(acc, entries);
+      const item = items[entries[0][0]];
+      assert(item.type === "ChatListItem");
+      expect(item.summaryText2).to.equal("Keine Nachrichten.");
+    });
+    it("unknown stock string id fails", async function () {
+      const error = await dc.rpc
+        .setStockStrings({ 1: "Nichts.", 999999: "unknown" })
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.UNKNOWN_STOCK_STRING);
+      expect(error.data).to.deep.equal({
+        kind: "UnknownStockString",
+        id: 999999,
+      });
+    });
+    it("invalid placeholder fails without setting any translation", async function () {
+      // 2 is "Me", it has no placeholder
+      const error = await dc.rpc
+        .setStockStrings({ 1: "Nichts.", 2: "Ich %1" })
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.GENERIC);
+      const acc = await dc.rpc.addAccount();
+      const contactId = await dc.rpc.contactsCreateContact(
+        acc,
+        "untouched@delta.chat",
+        null
+      );
+      await dc.rpc.contactsCreateChatByContactId(acc, contactId);
+      const entries = await dc.rpc.getChatlistEntries(acc, null, null, null);
+      const items = await dc.rpc.getChatlistItemsByEntries(acc, entries);
+      const item = items[entries[0][0]];
+      assert(item.type === "ChatListItem");
+      expect(item.summaryText2).to.equal("Keine Nachrichten.");
+    });
+  });
+
+  describe("account list events", function () {
+    function nextEvent(type: ServerEvent["type"]) {
+      return new Promise<ServerEvent>((resolve) =>
+        dc.serverEvents.once(type, resolve)
+      );
+    }
+    it("account added, selected and removed", async function () {
+      const added = nextEvent("AccountAdded");
+      const acc = await dc.rpc.addAccount();
+      expect(await added).to.deep.equal({
+        type: "AccountAdded",
+        accountId: acc,
+      });
+
+      const selected = nextEvent("AccountSelected");
+      await dc.rpc.selectAccount(acc);
+      expect(await selected).to.deep.equal({
+        type: "AccountSelected",
+        accountId: acc,
+      });
+
+      const removed = nextEvent("AccountRemoved");
+      await dc.rpc.removeAccount(acc);
+      expect(await removed).to.deep.equal({
+        type: "AccountRemoved",
+        accountId: acc,
+      });
+    });
+  });
+
+  describe("webxdc", function () {
+    let acc: number;
+    before(async () => {
+      acc = await dc.rpc.addAccount();
+    });
+    it("unknown message", async function () {
+      const infoError = await dc.rpc
+        .messageGetWebxdcInfo(acc, 9999)
+        .catch((e) => e);
+      expect(infoError.code).to.equal(ErrorCode.MESSAGE_NOT_FOUND);
+      const blobError = await dc.rpc
+        .webxdcGetBlob(acc, 9999, "index.html")
+        .catch((e) => e);
+      expect(blobError.code).to.equal(ErrorCode.MESSAGE_NOT_FOUND);
+    });
+    it("status updates of a message that is no webxdc", async function () {
+      const messageId = await dc.rpc.addDeviceMessage(acc, null, "text", null);
+      assert(messageId !== null);
+      const error = await dc.rpc
+        .webxdcGetStatusUpdates(acc, messageId, 0)
+        .catch((e) => e);
+      expect(error.code).to.equal(ErrorCode.NOT_WEBXDC_MESSAGE);
+      expect(error.data).to.deep.equal({
+        kind: "NotWebxdcMessage",
+        messageId,
+      });
+    });
   });
 });